@@ -0,0 +1,97 @@
+//! Errors that the axum examples' handlers can return, sent to the client
+//! as JSON.
+
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::Serialize;
+
+/// An error response, serialized as `{ "error": "message", "code": 404 }`.
+#[derive(Debug)]
+pub struct AppError {
+    status: StatusCode,
+    message: String,
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+    error: String,
+    code: u16,
+}
+
+impl AppError {
+    pub fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self { status, message: message.into() }
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, message)
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let body = ErrorBody { error: self.message, code: self.status.as_u16() };
+        (self.status, Json(body)).into_response()
+    }
+}
+
+#[cfg(feature = "sqlx")]
+impl From<sqlx::Error> for AppError {
+    fn from(err: sqlx::Error) -> Self {
+        match err {
+            sqlx::Error::RowNotFound => Self::not_found("Not found"),
+            // The details stay in the server log rather than going to the client
+            err => {
+                tracing::error!("Database error: {err}");
+                Self::new(StatusCode::INTERNAL_SERVER_ERROR, "Database error")
+            }
+        }
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(err: std::io::Error) -> Self {
+        match err.kind() {
+            std::io::ErrorKind::NotFound => Self::not_found("Not found"),
+            _ => {
+                tracing::error!("I/O error: {err}");
+                Self::new(StatusCode::INTERNAL_SERVER_ERROR, "I/O error")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    async fn body_json(err: AppError) -> (StatusCode, serde_json::Value) {
+        let response = err.into_response();
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_into_response() {
+        let (status, json) = body_json(AppError::new(StatusCode::BAD_REQUEST, "Bad id")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(json, serde_json::json!({ "error": "Bad id", "code": 400 }));
+    }
+
+    #[cfg(feature = "sqlx")]
+    #[test]
+    fn test_sqlx_conversions() {
+        assert_eq!(AppError::from(sqlx::Error::RowNotFound).into_response().status(), StatusCode::NOT_FOUND);
+        assert_eq!(AppError::from(sqlx::Error::PoolClosed).into_response().status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[test]
+    fn test_io_conversions() {
+        let missing = std::io::Error::from(std::io::ErrorKind::NotFound);
+        assert_eq!(AppError::from(missing).into_response().status(), StatusCode::NOT_FOUND);
+        let denied = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        assert_eq!(AppError::from(denied).into_response().status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
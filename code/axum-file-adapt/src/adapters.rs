@@ -0,0 +1,209 @@
+use pin_project_lite::pin_project;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::BufReader;
+use tokio_stream::wrappers::LinesStream;
+use tokio_stream::Stream;
+
+pin_project! {
+    /// Applies `transform` to each line of a line stream, putting the newline
+    /// back on the end so the result can be streamed straight to a client.
+    pub struct LineTransform<S, F> {
+        #[pin]
+        stream: S,
+        transform: F,
+    }
+}
+
+impl<S, F> LineTransform<S, F>
+where
+    S: Stream<Item = std::io::Result<String>>,
+    F: FnMut(String) -> String,
+{
+    pub fn new(stream: S, transform: F) -> Self {
+        Self { stream, transform }
+    }
+}
+
+/// Upper-cases every line. This was the original, hard-coded adapter.
+pub type ToUpper<S = LinesStream<BufReader<tokio::fs::File>>> = LineTransform<S, fn(String) -> String>;
+
+impl<S> LineTransform<S, fn(String) -> String>
+where
+    S: Stream<Item = std::io::Result<String>>,
+{
+    pub fn to_upper(stream: S) -> Self {
+        Self::new(stream, |line| line.to_uppercase())
+    }
+}
+
+impl<S, F> Stream for LineTransform<S, F>
+where
+    S: Stream<Item = std::io::Result<String>>,
+    F: FnMut(String) -> String,
+{
+    type Item = std::io::Result<String>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let transform = this.transform;
+        this.stream.poll_next(cx).map(|opt| {
+            opt.map(|res| {
+                res.map(|line| {
+                    transform(line) + "\n"
+                })
+            })
+        })
+    }
+}
+
+pin_project! {
+    /// Passes through only the lines for which `predicate` returns `true`.
+    /// Errors from the inner stream are always passed through.
+    pub struct FilterLines<S, P> {
+        #[pin]
+        stream: S,
+        predicate: P,
+    }
+}
+
+impl<S, P> FilterLines<S, P>
+where
+    S: Stream<Item = std::io::Result<String>>,
+    P: FnMut(&str) -> bool,
+{
+    pub fn new(stream: S, predicate: P) -> Self {
+        Self { stream, predicate }
+    }
+}
+
+impl<S, P> Stream for FilterLines<S, P>
+where
+    S: Stream<Item = std::io::Result<String>>,
+    P: FnMut(&str) -> bool,
+{
+    type Item = std::io::Result<String>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        // Keep pulling lines until one matches. If the inner stream isn't
+        // ready, it has registered our waker, so we can return `Pending`.
+        loop {
+            match this.stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(Ok(line))) => {
+                    if (this.predicate)(&line) {
+                        return Poll::Ready(Some(Ok(line)));
+                    }
+                }
+                other => return other,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio::io::AsyncBufReadExt;
+    use tokio_stream::StreamExt;
+
+    const TEXT: &[u8] = b"Hello\n  World  \nthird line";
+
+    fn lines() -> LinesStream<BufReader<&'static [u8]>> {
+        LinesStream::new(BufReader::new(TEXT).lines())
+    }
+
+    async fn collect<S: Stream<Item = std::io::Result<String>>>(stream: S) -> Vec<String> {
+        let stream = std::pin::pin!(stream);
+        stream.map(|line| line.unwrap()).collect().await
+    }
+
+    #[tokio::test]
+    async fn test_to_upper() {
+        let result = collect(ToUpper::to_upper(lines())).await;
+        assert_eq!(result, vec!["HELLO\n", "  WORLD  \n", "THIRD LINE\n"]);
+    }
+
+    #[tokio::test]
+    async fn test_trim() {
+        let result = collect(LineTransform::new(lines(), |line| line.trim().to_string())).await;
+        assert_eq!(result, vec!["Hello\n", "World\n", "third line\n"]);
+    }
+
+    #[tokio::test]
+    async fn test_line_numbers() {
+        let mut n = 0;
+        let numbered = LineTransform::new(lines(), move |line| {
+            n += 1;
+            format!("{n}: {line}")
+        });
+        let result = collect(numbered).await;
+        assert_eq!(result, vec!["1: Hello\n", "2:   World  \n", "3: third line\n"]);
+    }
+
+    const MULTILINE: &[u8] = b"[package]\n\nname = \"demo\"\n\n[dependencies]\naxum = \"0.6\"\n";
+
+    fn multiline() -> LinesStream<BufReader<&'static [u8]>> {
+        LinesStream::new(BufReader::new(MULTILINE).lines())
+    }
+
+    #[tokio::test]
+    async fn test_filter_non_empty() {
+        let result = collect(FilterLines::new(multiline(), |line| !line.is_empty())).await;
+        assert_eq!(result, vec!["[package]", "name = \"demo\"", "[dependencies]", "axum = \"0.6\""]);
+    }
+
+    #[tokio::test]
+    async fn test_filter_contains() {
+        let result = collect(FilterLines::new(multiline(), |line| line.contains('['))).await;
+        assert_eq!(result, vec!["[package]", "[dependencies]"]);
+    }
+
+    #[tokio::test]
+    async fn test_filter_nothing_matches() {
+        let result = collect(FilterLines::new(multiline(), |_| false)).await;
+        assert!(result.is_empty());
+    }
+
+    pin_project! {
+        /// Returns `Pending` (and immediately wakes) before every item, like a
+        /// slow reader would.
+        struct Hesitant<S> {
+            #[pin]
+            stream: S,
+            ready: bool,
+        }
+    }
+
+    impl<S: Stream> Stream for Hesitant<S> {
+        type Item = S::Item;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            let this = self.project();
+            if !*this.ready {
+                *this.ready = true;
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            *this.ready = false;
+            this.stream.poll_next(cx)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_filter_pending_inner_stream() {
+        let hesitant = Hesitant { stream: multiline(), ready: false };
+        let filtered = FilterLines::new(hesitant, |line| line.contains('='));
+
+        let mut filtered = std::pin::pin!(filtered);
+        // The very first poll must surface the inner `Pending` rather than spin
+        std::future::poll_fn(|cx| {
+            assert!(filtered.as_mut().poll_next(cx).is_pending());
+            Poll::Ready(())
+        })
+        .await;
+
+        let result = collect(filtered).await;
+        assert_eq!(result, vec!["name = \"demo\"", "axum = \"0.6\""]);
+    }
+}
@@ -1,17 +1,22 @@
 use axum::{
     body::StreamBody,
-    http::{HeaderMap, header, StatusCode},
+    extract::Query,
+    http::StatusCode,
     response::IntoResponse,
     routing::get,
     Router,
 };
 use tokio::io::BufReader;
 use std::net::SocketAddr;
-use pin_project_lite::pin_project;
+
+mod adapters;
+use adapters::{FilterLines, LineTransform, ToUpper};
 
 #[tokio::main]
 async fn main() {
-    let app = Router::new().route("/", get(handler));
+    let app = Router::new()
+        .route("/", get(handler))
+        .route("/filter", get(filter_handler));
 
     let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
     axum::Server::bind(&addr)
@@ -20,58 +25,108 @@ async fn main() {
         .unwrap();
 }
 
-pin_project! {
-    struct ToUpper {
-        #[pin]
-        stream: tokio_stream::wrappers::LinesStream<BufReader<tokio::fs::File>>,
-    }
-}
-
-impl ToUpper {
-    fn new(stream: tokio_stream::wrappers::LinesStream<BufReader<tokio::fs::File>>) -> Self {
-        Self { stream }
-    }
+#[derive(serde::Deserialize)]
+struct FileParams {
+    /// Path of the file to send, relative to the working directory
+    file: Option<String>,
 }
 
-impl tokio_stream::Stream for ToUpper {
-    type Item = std::io::Result<String>;
-
-    fn poll_next(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<Option<Self::Item>> {
-        self.project().stream.poll_next(cx).map(|opt| {
-            opt.map(|res| {
-                res.map(|line| {
-                    line.to_uppercase() + "\n"
-                })
-            })
-        })
-    }
-}
-
-async fn handler() -> impl IntoResponse {
+async fn handler(Query(params): Query<FileParams>) -> impl IntoResponse {
     use tokio::io::AsyncBufReadExt;
 
+    let path = file_helpers::requested_path(params.file.as_deref().unwrap_or(file_helpers::DEFAULT_FILE))?;
+
     // `File` implements `AsyncRead`
-    let file = match tokio::fs::File::open("Cargo.toml").await {
+    let file = match tokio::fs::File::open(&path).await {
         Ok(file) => file,
         Err(err) => return Err((StatusCode::NOT_FOUND, format!("File not found: {}", err))),
     };
     // convert the `AsyncRead` into a buffered reader, then a line stream, then your adapter
     let stream = BufReader::new(file).lines();
     let stream = tokio_stream::wrappers::LinesStream::new(stream);
-    let stream = ToUpper::new(stream);
+    let stream = ToUpper::to_upper(stream);
 
     // convert the `Stream` into an `axum::body::HttpBody`
     let body = StreamBody::new(stream);
 
-    let mut headers = HeaderMap::new();
-    headers.insert(
-        header::CONTENT_TYPE,
-        header::HeaderValue::from_static("text/toml; charset=utf-8"),
-    );
-    headers.insert(
-        header::CONTENT_DISPOSITION,
-        header::HeaderValue::from_str("attachment; filename=\"Cargo.toml\"").unwrap()
-    );
+    let headers = file_helpers::file_headers(&path)?;
 
     Ok((headers, body))
-}
\ No newline at end of file
+}
+
+#[derive(serde::Deserialize)]
+struct FilterParams {
+    /// Path of the file to send, relative to the working directory
+    file: Option<String>,
+    /// Only lines containing this text are sent. Without it, blank lines are
+    /// dropped.
+    contains: Option<String>,
+}
+
+async fn filter_handler(Query(params): Query<FilterParams>) -> impl IntoResponse {
+    use tokio::io::AsyncBufReadExt;
+
+    let path = file_helpers::requested_path(params.file.as_deref().unwrap_or(file_helpers::DEFAULT_FILE))?;
+
+    let file = match tokio::fs::File::open(&path).await {
+        Ok(file) => file,
+        Err(err) => return Err((StatusCode::NOT_FOUND, format!("File not found: {}", err))),
+    };
+    let stream = BufReader::new(file).lines();
+    let stream = tokio_stream::wrappers::LinesStream::new(stream);
+    let stream = FilterLines::new(stream, move |line| match &params.contains {
+        Some(needle) => line.contains(needle.as_str()),
+        None => !line.trim().is_empty(),
+    });
+    // Put the newlines back
+    let stream = LineTransform::new(stream, |line| line);
+
+    let headers = file_helpers::file_headers(&path)?;
+
+    Ok((headers, StreamBody::new(stream)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use axum::http::header;
+    use axum::response::Response;
+
+    async fn get_file(file: &str) -> Response {
+        handler(Query(FileParams { file: Some(file.to_string()) }))
+            .await
+            .into_response()
+    }
+
+    #[tokio::test]
+    async fn test_text_file() {
+        let mut file = tempfile::Builder::new().suffix(".txt").tempfile_in(".").unwrap();
+        std::io::Write::write_all(&mut file, b"Hello\nWorld\n").unwrap();
+        let name = file.path().file_name().unwrap().to_str().unwrap().to_string();
+
+        let response = get_file(&name).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let headers = response.headers();
+        assert_eq!(headers[header::CONTENT_TYPE], "text/plain; charset=utf-8");
+        assert_eq!(
+            headers[header::CONTENT_DISPOSITION],
+            format!("attachment; filename=\"{name}\"").as_str()
+        );
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(&body[..], b"HELLO\nWORLD\n");
+    }
+
+    #[tokio::test]
+    async fn test_rejects_traversal() {
+        assert_eq!(get_file("../Cargo.toml").await.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(get_file("/etc/passwd").await.status(), StatusCode::BAD_REQUEST);
+
+        let response = filter_handler(Query(FilterParams {
+            file: Some("../../Cargo.toml".to_string()),
+            contains: None,
+        }))
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,118 @@
+//! Serves any file beneath the configured root directory.
+
+use crate::SharedState;
+use axum::{
+    body::StreamBody,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use std::path::{Path as FsPath, PathBuf};
+use tokio_util::io::ReaderStream;
+
+/// Resolves `requested` against `root`, following any symlinks, and checks
+/// the result is still inside `root`.
+///
+/// Missing files give 404, and anything that ends up outside the root gives
+/// 403.
+pub async fn resolve_under_root(
+    root: &FsPath,
+    requested: &str,
+) -> Result<PathBuf, (StatusCode, String)> {
+    let internal_error = |err: std::io::Error| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string());
+    let root = tokio::fs::canonicalize(root).await.map_err(internal_error)?;
+    // Strip the leading slash so an absolute-looking path is still joined
+    // onto the root. `..` is left in place for canonicalize to resolve.
+    let path = match tokio::fs::canonicalize(root.join(requested.trim_start_matches('/'))).await {
+        Ok(path) => path,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Err((StatusCode::NOT_FOUND, format!("File not found: {requested}")))
+        }
+        Err(err) => return Err(internal_error(err)),
+    };
+    if !path.starts_with(&root) {
+        return Err((StatusCode::FORBIDDEN, format!("Outside the download root: {requested}")));
+    }
+    Ok(path)
+}
+
+/// `GET /download/*path`
+pub async fn download(
+    State(state): State<SharedState>,
+    Path(requested): Path<String>,
+) -> Result<Response, (StatusCode, String)> {
+    let path = resolve_under_root(&state.root, &requested).await?;
+    if !path.is_file() {
+        return Err((StatusCode::NOT_FOUND, format!("Not a file: {requested}")));
+    }
+    let file = match tokio::fs::File::open(&path).await {
+        Ok(file) => file,
+        Err(err) => return Err((StatusCode::NOT_FOUND, format!("File not found: {}", err))),
+    };
+    let headers = file_helpers::file_headers(&path)?;
+    Ok((headers, StreamBody::new(ReaderStream::new(file))).into_response())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::AppState;
+    use axum::http::header;
+    use std::sync::Arc;
+
+    /// A root containing `nested/dir/hello.html`, next to a file that
+    /// shouldn't be reachable.
+    fn temp_root() -> (tempfile::TempDir, SharedState) {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("root");
+        std::fs::create_dir_all(root.join("nested/dir")).unwrap();
+        std::fs::write(root.join("nested/dir/hello.html"), "<p>Hello</p>").unwrap();
+        std::fs::write(dir.path().join("secret.txt"), "secret").unwrap();
+        let state = AppState {
+            root,
+            upload_dir: dir.path().join("uploads"),
+            max_upload: 0,
+        };
+        (dir, Arc::new(state))
+    }
+
+    async fn get(state: &SharedState, path: &str) -> Response {
+        download(State(state.clone()), Path(path.to_string()))
+            .await
+            .into_response()
+    }
+
+    #[tokio::test]
+    async fn test_nested_file() {
+        let (_dir, state) = temp_root();
+        let response = get(&state, "nested/dir/hello.html").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "text/html; charset=utf-8");
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(&body[..], b"<p>Hello</p>");
+    }
+
+    #[tokio::test]
+    async fn test_missing_file() {
+        let (_dir, state) = temp_root();
+        assert_eq!(get(&state, "nested/nope.txt").await.status(), StatusCode::NOT_FOUND);
+        assert_eq!(get(&state, "nested/dir").await.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_escape_attempt() {
+        let (_dir, state) = temp_root();
+        assert_eq!(get(&state, "../secret.txt").await.status(), StatusCode::FORBIDDEN);
+        assert_eq!(get(&state, "nested/../../secret.txt").await.status(), StatusCode::FORBIDDEN);
+        // Still resolved inside the root, so it's allowed
+        assert_eq!(get(&state, "/nested/dir/../dir/hello.html").await.status(), StatusCode::OK);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_symlink_escape() {
+        let (dir, state) = temp_root();
+        std::os::unix::fs::symlink(dir.path().join("secret.txt"), state.root.join("link.txt")).unwrap();
+        assert_eq!(get(&state, "link.txt").await.status(), StatusCode::FORBIDDEN);
+    }
+}
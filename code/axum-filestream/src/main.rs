@@ -1,16 +1,46 @@
 use axum::{
     body::StreamBody,
-    http::{HeaderMap, header, StatusCode},
-    response::IntoResponse,
-    routing::get,
+    extract::Query,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
     Router,
 };
 use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use async_compression::tokio::bufread::GzipEncoder;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, BufReader};
 use tokio_util::io::ReaderStream;
 
+mod download;
+mod range;
+mod upload;
+
+/// Settings shared by the handlers.
+pub struct AppState {
+    /// Directory that `/download` serves files from
+    pub root: PathBuf,
+    /// Directory that `/upload` writes files to
+    pub upload_dir: PathBuf,
+    /// Largest upload accepted, in bytes
+    pub max_upload: u64,
+}
+
+pub type SharedState = Arc<AppState>;
+
 #[tokio::main]
 async fn main() {
-    let app = Router::new().route("/", get(handler));
+    // Serve downloads from $FILE_ROOT, or the working directory
+    let root = std::env::var_os("FILE_ROOT").map_or_else(|| PathBuf::from("."), PathBuf::from);
+    // Uploads go to $UPLOAD_DIR, or `uploads`, and are limited to
+    // $MAX_UPLOAD_BYTES, or 10MB
+    let upload_dir = std::env::var_os("UPLOAD_DIR").map_or_else(|| PathBuf::from("uploads"), PathBuf::from);
+    let max_upload = std::env::var("MAX_UPLOAD_BYTES")
+        .ok()
+        .and_then(|max| max.parse().ok())
+        .unwrap_or(10 * 1024 * 1024);
+    let app = build_app(Arc::new(AppState { root, upload_dir, max_upload }));
 
     let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
     axum::Server::bind(&addr)
@@ -19,26 +49,292 @@ async fn main() {
         .unwrap();
 }
 
-async fn handler() -> impl IntoResponse {
+fn build_app(state: SharedState) -> Router {
+    Router::new()
+        .route("/", get(handler))
+        .route("/download/*path", get(download::download))
+        .route("/upload/:name", post(upload::upload))
+        .with_state(state)
+}
+
+#[derive(serde::Deserialize)]
+struct FileParams {
+    /// Path of the file to send, relative to the working directory
+    file: Option<String>,
+}
+
+/// Whether the client's `Accept-Encoding` header lists gzip without
+/// refusing it via `q=0`.
+fn accepts_gzip(headers: &HeaderMap) -> bool {
+    let Some(accept) = headers.get(header::ACCEPT_ENCODING).and_then(|v| v.to_str().ok()) else {
+        return false;
+    };
+    accept.split(',').any(|encoding| {
+        let mut parts = encoding.split(';').map(str::trim);
+        let name = parts.next().unwrap_or_default();
+        let refused = parts.any(|param| {
+            param
+                .strip_prefix("q=")
+                .and_then(|q| q.parse::<f32>().ok())
+                .is_some_and(|q| q == 0.0)
+        });
+        (name.eq_ignore_ascii_case("gzip") || name == "*") && !refused
+    })
+}
+
+async fn handler(
+    Query(params): Query<FileParams>,
+    request_headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let path = file_helpers::requested_path(params.file.as_deref().unwrap_or(file_helpers::DEFAULT_FILE))?;
+
     // `File` implements `AsyncRead`
-    let file = match tokio::fs::File::open("Cargo.toml").await {
+    let mut file = match tokio::fs::File::open(&path).await {
         Ok(file) => file,
         Err(err) => return Err((StatusCode::NOT_FOUND, format!("File not found: {}", err))),
     };
-    // convert the `AsyncRead` into a `Stream`
-    let stream = ReaderStream::new(file);
-    // convert the `Stream` into an `axum::body::HttpBody`
-    let body = StreamBody::new(stream);
+    let mut headers = file_helpers::file_headers(&path)?;
+    headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    headers.insert(header::VARY, HeaderValue::from_static("accept-encoding"));
+
+    let Some(range) = request_headers.get(header::RANGE) else {
+        if accepts_gzip(&request_headers) {
+            // compress on the fly as the file is read
+            let stream = ReaderStream::new(GzipEncoder::new(BufReader::new(file)));
+            headers.insert(header::CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+            return Ok((headers, StreamBody::new(stream)).into_response());
+        }
+        // convert the `AsyncRead` into a `Stream`
+        let stream = ReaderStream::new(file);
+        // convert the `Stream` into an `axum::body::HttpBody`
+        let body = StreamBody::new(stream);
+        return Ok((headers, body).into_response());
+    };
+
+    let internal_error = |err: std::io::Error| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string());
+    let file_len = file.metadata().await.map_err(internal_error)?.len();
+    let range = range
+        .to_str()
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid Range header".to_string()))
+        .and_then(|range| range::parse_range(range, file_len));
+    let range = match range {
+        Ok(range) => range,
+        Err((StatusCode::RANGE_NOT_SATISFIABLE, message)) => {
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                header::CONTENT_RANGE,
+                HeaderValue::from_str(&format!("bytes */{file_len}")).unwrap(),
+            );
+            return Ok((StatusCode::RANGE_NOT_SATISFIABLE, headers, message).into_response());
+        }
+        Err(err) => return Err(err),
+    };
+
+    // Ranges are always served uncompressed, so the offsets refer to the file itself.
+    // Skip to the start of the range and stop reading after its last byte
+    file.seek(std::io::SeekFrom::Start(range.start))
+        .await
+        .map_err(internal_error)?;
+    let stream = ReaderStream::new(file.take(range.len()));
 
-    let mut headers = HeaderMap::new();
-    headers.insert(
-        header::CONTENT_TYPE,
-        header::HeaderValue::from_static("text/toml; charset=utf-8"),
-    );
     headers.insert(
-        header::CONTENT_DISPOSITION,
-        header::HeaderValue::from_str("attachment; filename=\"Cargo.toml\"").unwrap()
+        header::CONTENT_RANGE,
+        HeaderValue::from_str(&range.content_range(file_len)).unwrap(),
     );
+    headers.insert(header::CONTENT_LENGTH, HeaderValue::from(range.len()));
+    Ok((StatusCode::PARTIAL_CONTENT, headers, StreamBody::new(stream)).into_response())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    async fn get_file(file: &str) -> Response {
+        handler(Query(FileParams { file: Some(file.to_string()) }), HeaderMap::new())
+            .await
+            .into_response()
+    }
+
+    async fn get_range(file: &str, range: &str) -> Response {
+        get_with(file, header::RANGE, range).await
+    }
+
+    async fn get_with(file: &str, name: header::HeaderName, value: &str) -> Response {
+        let mut headers = HeaderMap::new();
+        headers.insert(name, HeaderValue::from_str(value).unwrap());
+        handler(Query(FileParams { file: Some(file.to_string()) }), headers)
+            .await
+            .into_response()
+    }
 
-    Ok((headers, body))
-}
\ No newline at end of file
+    fn temp_text_file(contents: &[u8]) -> (tempfile::NamedTempFile, String) {
+        let mut file = tempfile::Builder::new().suffix(".txt").tempfile_in(".").unwrap();
+        std::io::Write::write_all(&mut file, contents).unwrap();
+        let name = file.path().file_name().unwrap().to_str().unwrap().to_string();
+        (file, name)
+    }
+
+    #[tokio::test]
+    async fn test_text_file() {
+        let (_file, name) = temp_text_file(b"Hello, streaming world!");
+
+        let response = get_file(&name).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let headers = response.headers();
+        assert_eq!(headers[header::CONTENT_TYPE], "text/plain; charset=utf-8");
+        assert_eq!(
+            headers[header::CONTENT_DISPOSITION],
+            format!("attachment; filename=\"{name}\"").as_str()
+        );
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(&body[..], b"Hello, streaming world!");
+    }
+
+    #[tokio::test]
+    async fn test_default_file() {
+        let response = handler(Query(FileParams { file: None }), HeaderMap::new())
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_DISPOSITION],
+            "attachment; filename=\"Cargo.toml\""
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejects_traversal() {
+        assert_eq!(get_file("../Cargo.toml").await.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(get_file("src/../../Cargo.toml").await.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(get_file("/etc/passwd").await.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_missing_file() {
+        assert_eq!(get_file("nope.txt").await.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_range_request() {
+        let (_file, name) = temp_text_file(b"Hello, streaming world!");
+
+        let response = get_range(&name, "bytes=7-15").await;
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        let headers = response.headers();
+        assert_eq!(headers[header::CONTENT_RANGE], "bytes 7-15/23");
+        assert_eq!(headers[header::CONTENT_LENGTH], "9");
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(&body[..], b"streaming");
+    }
+
+    #[tokio::test]
+    async fn test_open_and_suffix_ranges() {
+        let (_file, name) = temp_text_file(b"Hello, streaming world!");
+
+        let response = get_range(&name, "bytes=17-").await;
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(&body[..], b"world!");
+
+        let response = get_range(&name, "bytes=-6").await;
+        assert_eq!(response.headers()[header::CONTENT_RANGE], "bytes 17-22/23");
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(&body[..], b"world!");
+    }
+
+    #[tokio::test]
+    async fn test_unsatisfiable_range() {
+        let (_file, name) = temp_text_file(b"Hello, streaming world!");
+
+        let response = get_range(&name, "bytes=100-200").await;
+        assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(response.headers()[header::CONTENT_RANGE], "bytes */23");
+
+        let response = get_range(&name, "lines=1-2").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_no_range_is_whole_file() {
+        let (_file, name) = temp_text_file(b"Hello, streaming world!");
+        let response = get_file(&name).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::ACCEPT_RANGES], "bytes");
+        assert!(response.headers().get(header::CONTENT_RANGE).is_none());
+    }
+
+    #[test]
+    fn test_accepts_gzip() {
+        let accepts = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::ACCEPT_ENCODING, HeaderValue::from_str(value).unwrap());
+            accepts_gzip(&headers)
+        };
+        assert!(accepts("gzip"));
+        assert!(accepts("deflate, gzip;q=0.8, br"));
+        assert!(accepts("*"));
+        assert!(!accepts("br, deflate"));
+        assert!(!accepts("gzip;q=0"));
+        assert!(!accepts_gzip(&HeaderMap::new()));
+    }
+
+    #[tokio::test]
+    async fn test_gzip() {
+        let contents = "All happy families are alike.\n".repeat(100);
+        let (_file, name) = temp_text_file(contents.as_bytes());
+
+        let response = get_with(&name, header::ACCEPT_ENCODING, "gzip, deflate").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert!(body.len() < contents.len());
+
+        let mut decoded = String::new();
+        std::io::Read::read_to_string(&mut flate2::read::GzDecoder::new(&body[..]), &mut decoded)
+            .unwrap();
+        assert_eq!(decoded, contents);
+    }
+
+    #[tokio::test]
+    async fn test_no_gzip_without_accept_encoding() {
+        let (_file, name) = temp_text_file(b"Hello, streaming world!");
+        let response = get_with(&name, header::ACCEPT_ENCODING, "br").await;
+        assert!(response.headers().get(header::CONTENT_ENCODING).is_none());
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(&body[..], b"Hello, streaming world!");
+    }
+
+    #[tokio::test]
+    async fn test_build_app() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("hello.txt"), "Hello from the router").unwrap();
+        let state = Arc::new(AppState {
+            root: dir.path().to_path_buf(),
+            upload_dir: dir.path().join("uploads"),
+            max_upload: 1024,
+        });
+
+        let response = build_app(state.clone())
+            .oneshot(Request::get("/download/hello.txt").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(&body[..], b"Hello from the router");
+
+        let response = build_app(state.clone())
+            .oneshot(Request::get("/?file=Cargo.toml").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = build_app(state)
+            .oneshot(Request::get("/nowhere").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,141 @@
+use axum::http::StatusCode;
+
+/// An inclusive byte range within a file, as requested by a `Range` header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl ByteRange {
+    /// Number of bytes covered by the range.
+    pub fn len(&self) -> u64 {
+        self.end - self.start + 1
+    }
+
+    /// Value for the `Content-Range` header of a `206` response.
+    pub fn content_range(&self, file_len: u64) -> String {
+        format!("bytes {}-{}/{file_len}", self.start, self.end)
+    }
+}
+
+/// Parses a `Range: bytes=start-end` header against a file of `file_len`
+/// bytes. `start-` runs to the end of the file and `-n` selects the last `n`
+/// bytes; an `end` past the end of the file is clamped. Multiple ranges
+/// aren't supported.
+///
+/// Malformed headers are rejected with `400`, ranges that don't overlap the
+/// file with `416 Range Not Satisfiable`.
+pub fn parse_range(header: &str, file_len: u64) -> Result<ByteRange, (StatusCode, String)> {
+    let bad_request = || {
+        (
+            StatusCode::BAD_REQUEST,
+            format!("Invalid Range header: {header}"),
+        )
+    };
+    let unsatisfiable = || {
+        (
+            StatusCode::RANGE_NOT_SATISFIABLE,
+            format!("Range {header} is outside a file of {file_len} bytes"),
+        )
+    };
+
+    let spec = header
+        .trim()
+        .strip_prefix("bytes=")
+        .ok_or_else(bad_request)?;
+    let (start, end) = spec.split_once('-').ok_or_else(bad_request)?;
+    let parse = |n: &str| n.trim().parse::<u64>().map_err(|_| bad_request());
+
+    let range = match (start.trim().is_empty(), end.trim().is_empty()) {
+        (false, false) => {
+            let (start, end) = (parse(start)?, parse(end)?);
+            if end < start {
+                return Err(bad_request());
+            }
+            if start >= file_len {
+                return Err(unsatisfiable());
+            }
+            ByteRange {
+                start,
+                end: end.min(file_len - 1),
+            }
+        }
+        (false, true) => {
+            let start = parse(start)?;
+            if start >= file_len {
+                return Err(unsatisfiable());
+            }
+            ByteRange {
+                start,
+                end: file_len - 1,
+            }
+        }
+        (true, false) => {
+            let suffix = parse(end)?;
+            if suffix == 0 || file_len == 0 {
+                return Err(unsatisfiable());
+            }
+            ByteRange {
+                start: file_len.saturating_sub(suffix),
+                end: file_len - 1,
+            }
+        }
+        (true, true) => return Err(bad_request()),
+    };
+    Ok(range)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(
+            parse_range("bytes=0-4", 10),
+            Ok(ByteRange { start: 0, end: 4 })
+        );
+        assert_eq!(
+            parse_range("bytes=5-", 10),
+            Ok(ByteRange { start: 5, end: 9 })
+        );
+        assert_eq!(
+            parse_range("bytes=-3", 10),
+            Ok(ByteRange { start: 7, end: 9 })
+        );
+        assert_eq!(
+            parse_range("bytes=-30", 10),
+            Ok(ByteRange { start: 0, end: 9 })
+        );
+        assert_eq!(
+            parse_range("bytes=8-100", 10),
+            Ok(ByteRange { start: 8, end: 9 })
+        );
+    }
+
+    #[test]
+    fn test_len_and_content_range() {
+        let range = ByteRange { start: 2, end: 5 };
+        assert_eq!(range.len(), 4);
+        assert_eq!(range.content_range(10), "bytes 2-5/10");
+    }
+
+    #[test]
+    fn test_invalid_ranges() {
+        for header in ["items=0-4", "bytes=4", "bytes=a-b", "bytes=5-2", "bytes=-"] {
+            assert_eq!(
+                parse_range(header, 10).unwrap_err().0,
+                StatusCode::BAD_REQUEST,
+                "{header}"
+            );
+        }
+        for header in ["bytes=10-", "bytes=10-20", "bytes=-0"] {
+            assert_eq!(
+                parse_range(header, 10).unwrap_err().0,
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                "{header}"
+            );
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Streams uploaded files to disk.
+
+use crate::SharedState;
+use axum::{
+    extract::{BodyStream, Path, State},
+    http::{header, HeaderMap, StatusCode},
+    Json,
+};
+use futures_util::TryStreamExt;
+use tokio::io::AsyncReadExt;
+use tokio_util::io::StreamReader;
+
+/// What `POST /upload/:name` sends back.
+#[derive(Debug, serde::Serialize)]
+pub struct UploadResult {
+    pub bytes_written: u64,
+}
+
+fn too_large(max: u64) -> (StatusCode, String) {
+    (StatusCode::PAYLOAD_TOO_LARGE, format!("Uploads are limited to {max} bytes"))
+}
+
+/// `POST /upload/:name`
+///
+/// The body is written to a `.part` file in the upload directory as it
+/// arrives, and renamed to `name` once it's complete. If it grows past the
+/// configured limit, the partial file is deleted and the client gets a 413.
+pub async fn upload(
+    State(state): State<SharedState>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+    body: BodyStream,
+) -> Result<Json<UploadResult>, (StatusCode, String)> {
+    let max = state.max_upload;
+    let name = file_helpers::requested_path(&name)?;
+    // No point reading a body the client has already told us is too big
+    let declared = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|len| len.to_str().ok())
+        .and_then(|len| len.parse::<u64>().ok());
+    if declared.is_some_and(|len| len > max) {
+        return Err(too_large(max));
+    }
+
+    let internal_error = |err: std::io::Error| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string());
+    tokio::fs::create_dir_all(&state.upload_dir).await.map_err(internal_error)?;
+    let destination = state.upload_dir.join(&name);
+    let part = state
+        .upload_dir
+        .join(format!(".{}.part", name.display()));
+
+    let body = StreamReader::new(body.map_err(std::io::Error::other));
+    // Read one byte past the limit, so we can tell a body that's exactly the
+    // limit from one that's over it
+    let mut body = body.take(max + 1);
+    let mut file = tokio::fs::File::create(&part).await.map_err(internal_error)?;
+    let written = tokio::io::copy(&mut body, &mut file).await;
+    drop(file);
+
+    let written = match written {
+        Ok(written) if written <= max => written,
+        Ok(_) => {
+            let _ = tokio::fs::remove_file(&part).await;
+            return Err(too_large(max));
+        }
+        Err(err) => {
+            let _ = tokio::fs::remove_file(&part).await;
+            return Err((StatusCode::BAD_REQUEST, format!("Upload failed: {err}")));
+        }
+    };
+    tokio::fs::rename(&part, &destination).await.map_err(internal_error)?;
+    Ok(Json(UploadResult { bytes_written: written }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::AppState;
+    use axum::{body::Body, http::Request, Router};
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    fn app(max_upload: u64) -> (tempfile::TempDir, Router) {
+        let dir = tempfile::tempdir().unwrap();
+        let state = Arc::new(AppState {
+            root: dir.path().to_path_buf(),
+            upload_dir: dir.path().join("uploads"),
+            max_upload,
+        });
+        (dir, crate::build_app(state))
+    }
+
+    /// A body that arrives in several chunks, without a `Content-Length`.
+    fn chunked(chunks: Vec<&'static str>) -> Body {
+        let stream = futures_util::stream::iter(chunks.into_iter().map(Ok::<_, std::io::Error>));
+        Body::wrap_stream(stream)
+    }
+
+    async fn post_upload(app: Router, name: &str, body: Body) -> axum::response::Response {
+        app.oneshot(
+            Request::post(format!("/upload/{name}"))
+                .body(body)
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_upload() {
+        let (dir, app) = app(1024);
+        let response = post_upload(app, "hello.txt", chunked(vec!["Hello, ", "streaming ", "world!"])).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json, serde_json::json!({ "bytes_written": 23 }));
+
+        let saved = std::fs::read_to_string(dir.path().join("uploads/hello.txt")).unwrap();
+        assert_eq!(saved, "Hello, streaming world!");
+    }
+
+    #[tokio::test]
+    async fn test_upload_at_limit() {
+        let (_dir, app) = app(5);
+        let response = post_upload(app, "five.txt", chunked(vec!["12", "345"])).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_upload_too_large() {
+        let (dir, app) = app(10);
+        let response = post_upload(app, "big.txt", chunked(vec!["0123456789", "x"])).await;
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        // Neither the file nor its partial upload is left behind
+        let uploads: Vec<_> = std::fs::read_dir(dir.path().join("uploads")).unwrap().collect();
+        assert!(uploads.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_declared_length_too_large() {
+        let (dir, app) = app(10);
+        let response = post_upload(app, "big.txt", Body::from("far more than ten bytes")).await;
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(!dir.path().join("uploads/big.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_rejects_bad_name() {
+        let (_dir, app) = app(1024);
+        let response = post_upload(app, "..", Body::from("x")).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
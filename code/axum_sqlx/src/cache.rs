@@ -0,0 +1,253 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::HelloJson;
+
+struct CacheEntry {
+    message: HelloJson,
+    inserted: Instant,
+    /// Value of the cache's access clock when this entry was last read
+    last_used: u64,
+}
+
+pub struct MessageCache {
+    messages: HashMap<i64, CacheEntry>,
+    max_entries: Option<usize>,
+    ttl: Option<Duration>,
+    /// Ticks on every lookup, giving us a cheap least-recently-used order
+    clock: u64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+/// A snapshot of how well the cache is doing.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// `hits / (hits + misses)`, or 0 if there haven't been any lookups
+    pub hit_ratio: f64,
+}
+
+/// Configures a [`MessageCache`]. By default the cache is unbounded and
+/// entries never expire.
+#[derive(Default)]
+pub struct MessageCacheBuilder {
+    max_entries: Option<usize>,
+    ttl: Option<Duration>,
+}
+
+impl MessageCacheBuilder {
+    /// Caps the cache at `max_entries`, evicting the least recently used
+    /// entry to make room for new ones.
+    pub fn max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = Some(max_entries);
+        self
+    }
+
+    /// Entries older than `ttl` are treated as misses and re-fetched.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    pub fn build(self) -> MessageCache {
+        MessageCache {
+            messages: HashMap::new(),
+            max_entries: self.max_entries,
+            ttl: self.ttl,
+            clock: 0,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+}
+
+impl MessageCache {
+    #[allow(dead_code)]
+    pub fn new() -> Self {
+        Self::builder().build()
+    }
+
+    pub fn builder() -> MessageCacheBuilder {
+        MessageCacheBuilder::default()
+    }
+
+    pub async fn get(&mut self, id: i64, pool: &sqlx::SqlitePool) -> Result<Option<HelloJson>, sqlx::Error> {
+        self.clock += 1;
+
+        // Do we have a fresh cached entry?
+        if let Some(entry) = self.messages.get_mut(&id) {
+            let expired = self.ttl.is_some_and(|ttl| entry.inserted.elapsed() > ttl);
+            if !expired {
+                // Yes - return it
+                entry.last_used = self.clock;
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(Some(entry.message.clone()));
+            }
+            self.messages.remove(&id);
+        }
+
+        // No - look it up in the database
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let row = sqlx::query_as::<_, HelloJson>("SELECT * FROM messages WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        if let Some(row) = &row {
+            self.insert(row.clone());
+        }
+        Ok(row)
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let total = hits + misses;
+        CacheStats {
+            hits,
+            misses,
+            hit_ratio: if total == 0 { 0.0 } else { hits as f64 / total as f64 },
+        }
+    }
+
+    /// Drops the cached copy of `id`, so the next `get` reads the database.
+    pub fn invalidate(&mut self, id: i64) {
+        self.messages.remove(&id);
+    }
+
+    /// Drops every cached entry.
+    #[allow(dead_code)]
+    pub fn clear(&mut self) {
+        self.messages.clear();
+    }
+
+    fn insert(&mut self, message: HelloJson) {
+        if let Some(max_entries) = self.max_entries {
+            while self.messages.len() >= max_entries {
+                let Some(oldest) = self
+                    .messages
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.last_used)
+                    .map(|(id, _)| *id)
+                else {
+                    break;
+                };
+                self.messages.remove(&oldest);
+            }
+            if max_entries == 0 {
+                return;
+            }
+        }
+        self.messages.insert(
+            message.id,
+            CacheEntry {
+                message,
+                inserted: Instant::now(),
+                last_used: self.clock,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    async fn test_pool() -> sqlx::SqlitePool {
+        // Every connection to `sqlite::memory:` gets its own database, so
+        // keep the pool to a single connection.
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_get_caches() {
+        let pool = test_pool().await;
+        let mut cache = MessageCache::new();
+        assert_eq!(cache.get(1, &pool).await.unwrap().unwrap().message, "Hello World!");
+        assert!(cache.messages.contains_key(&1));
+        assert!(cache.get(999, &pool).await.unwrap().is_none());
+        assert!(!cache.messages.contains_key(&999));
+    }
+
+    #[tokio::test]
+    async fn test_stats() {
+        let pool = test_pool().await;
+        let mut cache = MessageCache::new();
+        assert_eq!(cache.stats(), CacheStats { hits: 0, misses: 0, hit_ratio: 0.0 });
+
+        cache.get(1, &pool).await.unwrap().unwrap();
+        assert_eq!(cache.stats(), CacheStats { hits: 0, misses: 1, hit_ratio: 0.0 });
+
+        cache.get(1, &pool).await.unwrap().unwrap();
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 1, hit_ratio: 0.5 });
+    }
+
+    #[tokio::test]
+    async fn test_invalidate() {
+        let pool = test_pool().await;
+        let mut cache = MessageCache::new();
+        cache.get(1, &pool).await.unwrap().unwrap();
+        sqlx::query("UPDATE messages SET message = 'Changed' WHERE id = 1")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(cache.get(1, &pool).await.unwrap().unwrap().message, "Hello World!");
+
+        cache.invalidate(1);
+        assert_eq!(cache.get(1, &pool).await.unwrap().unwrap().message, "Changed");
+    }
+
+    #[tokio::test]
+    async fn test_clear() {
+        let pool = test_pool().await;
+        let mut cache = MessageCache::new();
+        cache.get(1, &pool).await.unwrap().unwrap();
+        cache.get(2, &pool).await.unwrap().unwrap();
+        cache.clear();
+        assert!(cache.messages.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_evicts_least_recently_used() {
+        let pool = test_pool().await;
+        let mut cache = MessageCache::builder().max_entries(2).build();
+        cache.get(1, &pool).await.unwrap().unwrap();
+        cache.get(2, &pool).await.unwrap().unwrap();
+        // Touch 1 so that 2 becomes the least recently used
+        cache.get(1, &pool).await.unwrap().unwrap();
+        cache.get(3, &pool).await.unwrap().unwrap();
+
+        assert_eq!(cache.messages.len(), 2);
+        assert!(cache.messages.contains_key(&1));
+        assert!(!cache.messages.contains_key(&2));
+        assert!(cache.messages.contains_key(&3));
+    }
+
+    #[tokio::test]
+    async fn test_ttl_expires_entries() {
+        let pool = test_pool().await;
+        let mut cache = MessageCache::builder()
+            .ttl(Duration::from_millis(50))
+            .build();
+        cache.get(1, &pool).await.unwrap().unwrap();
+        sqlx::query("UPDATE messages SET message = 'Changed' WHERE id = 1")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        // Still inside the TTL, so we get the cached copy
+        assert_eq!(cache.get(1, &pool).await.unwrap().unwrap().message, "Hello World!");
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(cache.get(1, &pool).await.unwrap().unwrap().message, "Changed");
+    }
+}
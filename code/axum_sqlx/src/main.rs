@@ -1,33 +1,100 @@
-use axum::extract::Path;
+use axum::extract::{Path, Query};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
 use axum::{routing::get, Router, Json, Extension};
 use serde::{Serialize, Deserialize};
 use sqlx::FromRow;
 use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+mod cache;
+use app_error::AppError;
+use cache::{CacheStats, MessageCache};
+
+type SharedCache = Arc<Mutex<MessageCache>>;
 
 #[tokio::main]
 async fn main() {
-    let pool = sqlx::SqlitePool::connect("sqlite:hello_db.db").await.unwrap();
-    sqlx::migrate!("./migrations")
-        .run(&pool)
-        .await
-        .expect("Unable to migrate database");
+    tracing_subscriber::fmt::init();
+    let pool = init_pool().await.expect("Unable to open and migrate database");
 
-    let app = Router::new()
-        .route("/", get(say_hello_json))
-        .route("/one/:id", get(get_one))
-        .layer(Extension(pool))
-        .layer(Extension(
-            std::sync::Arc::new(
-                tokio::sync::Mutex::new(
-                    MessageCache::new()
-                )
-            )
-        ));
+    let app = build_app(pool.clone());
     let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
     axum::Server::bind(&addr)
         .serve(app.into_make_service())
+        .with_graceful_shutdown(shutdown_signal())
         .await
         .unwrap();
+
+    // Let SQLite checkpoint and close cleanly now that nothing is using it
+    pool.close().await;
+}
+
+const DEFAULT_DATABASE_URL: &str = "sqlite:hello_db.db";
+
+/// Connects to the database named by `DATABASE_URL` (defaulting to
+/// `sqlite:hello_db.db`) and runs the migrations. `DB_MAX_CONNECTIONS`
+/// optionally caps the pool size.
+async fn init_pool() -> Result<sqlx::SqlitePool, sqlx::Error> {
+    let url = std::env::var("DATABASE_URL").unwrap_or_else(|_| DEFAULT_DATABASE_URL.to_string());
+    let max = parse_max_connections(std::env::var("DB_MAX_CONNECTIONS").ok().as_deref())?;
+    init_pool_with(&url, max).await
+}
+
+/// Reads a `DB_MAX_CONNECTIONS` value, so a typo in the configuration is
+/// reported rather than quietly falling back to the default.
+fn parse_max_connections(value: Option<&str>) -> Result<Option<u32>, sqlx::Error> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    match value.trim().parse::<u32>() {
+        Ok(max) if max > 0 => Ok(Some(max)),
+        _ => Err(sqlx::Error::Configuration(
+            format!("DB_MAX_CONNECTIONS must be a positive whole number, not {value:?}").into(),
+        )),
+    }
+}
+
+/// Connects to `url`, with at most `max_connections` connections if given,
+/// and runs the migrations.
+async fn init_pool_with(url: &str, max_connections: Option<u32>) -> Result<sqlx::SqlitePool, sqlx::Error> {
+    let mut options = sqlx::sqlite::SqlitePoolOptions::new();
+    if let Some(max) = max_connections {
+        if max == 0 {
+            return Err(sqlx::Error::Configuration("The pool needs at least one connection".into()));
+        }
+        options = options.max_connections(max);
+    }
+    let pool = options.connect(url).await?;
+    sqlx::migrate!("./migrations").run(&pool).await?;
+    Ok(pool)
+}
+
+async fn shutdown_signal() {
+    tokio::signal::ctrl_c()
+        .await
+        .expect("Unable to listen for Ctrl-C");
+    println!("Shutting down");
+}
+
+fn build_app(pool: sqlx::SqlitePool) -> Router {
+    Router::new()
+        .route("/", get(say_hello_json).post(new_message))
+        .route("/one/:id", get(get_one).post(update_one))
+        .route("/cache/stats", get(cache_stats))
+        .layer(Extension(pool))
+        .layer(Extension(
+            Arc::new(
+                Mutex::new(
+                    MessageCache::builder()
+                        .max_entries(1000)
+                        .ttl(Duration::from_secs(60))
+                        .build()
+                )
+            )
+        ))
 }
 
 #[derive(Serialize, Deserialize, FromRow, Clone)]
@@ -36,55 +103,450 @@ struct HelloJson {
     message: String,
 }
 
+const DEFAULT_LIMIT: u32 = 50;
+const MAX_LIMIT: u32 = 500;
+
+/// `?limit=&offset=` query parameters. `limit` defaults to 50 and is capped
+/// at 500; `offset` defaults to 0.
+#[derive(Deserialize)]
+struct Pagination {
+    limit: Option<u32>,
+    offset: Option<u32>,
+}
+
 async fn say_hello_json(
+    Query(pagination): Query<Pagination>,
     Extension(pool): Extension<sqlx::SqlitePool>,
-) -> Json<Vec<HelloJson>> {
-    let result = sqlx::query_as::<_, HelloJson>("SELECT * FROM messages")
+) -> Result<Json<Vec<HelloJson>>, AppError> {
+    let limit = pagination.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+    let offset = pagination.offset.unwrap_or(0);
+    let result = sqlx::query_as::<_, HelloJson>("SELECT * FROM messages ORDER BY id LIMIT ? OFFSET ?")
+        .bind(limit)
+        .bind(offset)
         .fetch_all(&pool)
-        .await
-        .unwrap();
-    Json(result)
+        .await?;
+    Ok(Json(result))
+}
+
+/// Weak ETag for a message, derived from its id and text.
+///
+/// Uses 64-bit FNV-1a rather than `std`'s hasher, whose output may change
+/// between Rust releases and would invalidate every client's cached copy.
+fn etag_for(message: &HelloJson) -> String {
+    const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+    let bytes = message.id.to_le_bytes().into_iter().chain(message.message.bytes());
+    let hash = bytes.fold(FNV_OFFSET, |hash, byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME));
+    format!("W/\"{hash:016x}\"")
+}
+
+/// Does an `If-None-Match` header value match `etag`? Uses the weak
+/// comparison, so `W/` prefixes are ignored on both sides.
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    let etag = etag.trim_start_matches("W/");
+    if_none_match
+        .split(',')
+        .map(|candidate| candidate.trim())
+        .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
 }
 
 async fn get_one(
     Path(id): Path<i64>,
+    headers: HeaderMap,
     Extension(pool): Extension<sqlx::SqlitePool>,
-    Extension(cache): Extension<std::sync::Arc<tokio::sync::Mutex<MessageCache>>>,
-) -> Json<HelloJson> {
+    Extension(cache): Extension<SharedCache>,
+) -> Result<Response, AppError> {
     let mut lock = cache.lock().await;
-    Json(lock.get(id, &pool).await.unwrap())
+    let message = lock.get(id, &pool)
+        .await?
+        .ok_or_else(|| AppError::not_found(format!("No message with id {id}")))?;
+
+    let etag = etag_for(&message);
+    let etag_header = HeaderValue::from_str(&etag)
+        .map_err(|_| AppError::new(StatusCode::INTERNAL_SERVER_ERROR, "Invalid ETag"))?;
+    let not_modified = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| etag_matches(value, &etag));
+    if not_modified {
+        return Ok((StatusCode::NOT_MODIFIED, [(header::ETAG, etag_header)]).into_response());
+    }
+    Ok(([(header::ETAG, etag_header)], Json(message)).into_response())
 }
 
-use std::collections::HashMap;
+async fn cache_stats(Extension(cache): Extension<SharedCache>) -> Json<CacheStats> {
+    Json(cache.lock().await.stats())
+}
 
-struct MessageCache {
-    messages: HashMap<i64, HelloJson>
+#[derive(Deserialize)]
+struct NewMessage {
+    message: String,
 }
 
-impl MessageCache {
-    fn new() -> Self {
-        MessageCache {
-            messages: HashMap::new()
-        }
+/// Adds a message, returning it with its newly assigned id.
+async fn new_message(
+    Extension(pool): Extension<sqlx::SqlitePool>,
+    Json(new): Json<NewMessage>,
+) -> Result<(StatusCode, Json<HelloJson>), AppError> {
+    if new.message.trim().is_empty() {
+        return Err(AppError::new(StatusCode::UNPROCESSABLE_ENTITY, "Message can't be empty"));
+    }
+    let result = sqlx::query("INSERT INTO messages (message) VALUES (?)")
+        .bind(&new.message)
+        .execute(&pool)
+        .await?;
+    let created = HelloJson {
+        id: result.last_insert_rowid(),
+        message: new.message,
+    };
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+#[derive(Deserialize)]
+struct UpdateMessage {
+    message: String,
+}
+
+/// Changes the text of a message, and drops it from the cache so the next
+/// read sees the new value.
+async fn update_one(
+    Path(id): Path<i64>,
+    Extension(pool): Extension<sqlx::SqlitePool>,
+    Extension(cache): Extension<SharedCache>,
+    Json(update): Json<UpdateMessage>,
+) -> Result<Json<HelloJson>, AppError> {
+    let result = sqlx::query("UPDATE messages SET message = ? WHERE id = ?")
+        .bind(&update.message)
+        .bind(id)
+        .execute(&pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::not_found(format!("No message with id {id}")));
+    }
+    cache.lock().await.invalidate(id);
+    Ok(Json(HelloJson { id, message: update.message }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use axum::{body::Body, http::{self, Request}};
+    use tower::ServiceExt; // for `oneshot`
+
+    async fn test_pool() -> sqlx::SqlitePool {
+        // Every connection to `sqlite::memory:` gets its own database, so
+        // keep the pool to a single connection.
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        pool
+    }
+
+    async fn into_message(response: Response) -> HelloJson {
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_update_invalidates_cache() {
+        let pool = test_pool().await;
+        let cache: SharedCache = Arc::new(Mutex::new(MessageCache::new()));
+
+        let before = get_one(Path(1), HeaderMap::new(), Extension(pool.clone()), Extension(cache.clone())).await.unwrap();
+        assert_eq!(into_message(before).await.message, "Hello World!");
+
+        let update = UpdateMessage { message: "Hello Multiverse!".to_string() };
+        let Json(updated) = update_one(Path(1), Extension(pool.clone()), Extension(cache.clone()), Json(update))
+            .await
+            .unwrap();
+        assert_eq!(updated.message, "Hello Multiverse!");
+
+        let after = get_one(Path(1), HeaderMap::new(), Extension(pool), Extension(cache)).await.unwrap();
+        assert_eq!(into_message(after).await.message, "Hello Multiverse!");
     }
 
-    async fn get(&mut self, id: i64, pool: &sqlx::SqlitePool) -> Option<HelloJson> {
-        // Do we have a cached entry?
-        if let Some(msg) = self.messages.get(&id) {
-            // Yes - return it
-            Some(msg.clone())
-        } else {
-            // No - look it up in the database
-            let row = sqlx::query_as::<_, HelloJson>("SELECT * FROM messages WHERE id = ?")
+    async fn seed_messages(pool: &sqlx::SqlitePool, count: i64) {
+        sqlx::query("DELETE FROM messages").execute(pool).await.unwrap();
+        for id in 1..=count {
+            sqlx::query("INSERT INTO messages (id, message) VALUES (?, ?)")
                 .bind(id)
-                .fetch_one(pool)
-                .await;
-            if let Ok(row) = row {
-                self.messages.insert(row.id, row.clone());
-                Some(row)
-            } else {
-                None
-            }
+                .bind(format!("Message {id}"))
+                .execute(pool)
+                .await
+                .unwrap();
+        }
+    }
+
+    async fn page_ids(pool: &sqlx::SqlitePool, limit: Option<u32>, offset: Option<u32>) -> Vec<i64> {
+        let Json(rows) = say_hello_json(Query(Pagination { limit, offset }), Extension(pool.clone())).await.unwrap();
+        rows.iter().map(|row| row.id).collect()
+    }
+
+    #[tokio::test]
+    async fn test_pagination() {
+        let pool = test_pool().await;
+        seed_messages(&pool, 10).await;
+
+        assert_eq!(page_ids(&pool, None, None).await, (1..=10).collect::<Vec<_>>());
+        assert_eq!(page_ids(&pool, Some(3), None).await, vec![1, 2, 3]);
+        assert_eq!(page_ids(&pool, Some(3), Some(3)).await, vec![4, 5, 6]);
+        assert_eq!(page_ids(&pool, None, Some(8)).await, vec![9, 10]);
+        assert!(page_ids(&pool, Some(5), Some(20)).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_pagination_limits() {
+        let pool = test_pool().await;
+        seed_messages(&pool, 600).await;
+
+        assert_eq!(page_ids(&pool, None, None).await.len(), DEFAULT_LIMIT as usize);
+        assert_eq!(page_ids(&pool, Some(10_000), None).await.len(), MAX_LIMIT as usize);
+    }
+
+    #[tokio::test]
+    async fn test_get_one_missing_is_404() {
+        let pool = test_pool().await;
+        sqlx::query("DELETE FROM messages").execute(&pool).await.unwrap();
+        let cache: SharedCache = Arc::new(Mutex::new(MessageCache::new()));
+
+        let result = get_one(Path(99999), HeaderMap::new(), Extension(pool), Extension(cache)).await;
+        assert_eq!(result.unwrap_err().into_response().status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_cache_stats_route() {
+        let pool = test_pool().await;
+        let cache: SharedCache = Arc::new(Mutex::new(MessageCache::new()));
+        let _ = get_one(Path(2), HeaderMap::new(), Extension(pool.clone()), Extension(cache.clone())).await;
+        let _ = get_one(Path(2), HeaderMap::new(), Extension(pool), Extension(cache.clone())).await;
+
+        let Json(stats) = cache_stats(Extension(cache)).await;
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.hits, 1);
+    }
+
+    #[tokio::test]
+    async fn test_update_missing_row() {
+        let pool = test_pool().await;
+        let cache: SharedCache = Arc::new(Mutex::new(MessageCache::new()));
+        let update = UpdateMessage { message: "Nobody home".to_string() };
+        let result = update_one(Path(99999), Extension(pool), Extension(cache), Json(update)).await;
+        let Err(err) = result else {
+            panic!("Updated a row that doesn't exist");
+        };
+        assert_eq!(err.into_response().status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_init_pool_with() {
+        // Each in-memory connection is a separate database, so use just one
+        let pool = init_pool_with("sqlite::memory:", Some(1)).await.unwrap();
+        let rows: Vec<HelloJson> = sqlx::query_as("SELECT * FROM messages")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(pool.options().get_max_connections(), 1);
+
+        let err = init_pool_with("sqlite::memory:", Some(0)).await.unwrap_err();
+        assert!(matches!(err, sqlx::Error::Configuration(_)), "{err}");
+    }
+
+    #[test]
+    fn test_parse_max_connections() {
+        assert_eq!(parse_max_connections(None).unwrap(), None);
+        assert_eq!(parse_max_connections(Some("8")).unwrap(), Some(8));
+        for bad in ["0", "eight", "-1", ""] {
+            let err = parse_max_connections(Some(bad)).unwrap_err();
+            assert!(matches!(err, sqlx::Error::Configuration(_)), "{bad}: {err}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_build_app_serves_root() {
+        let app = build_app(test_pool().await);
+        let response = app
+            .oneshot(Request::builder().uri("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let rows: Vec<HelloJson> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(rows.len(), 3);
+    }
+
+    fn post_json(uri: &str, body: serde_json::Value) -> Request<Body> {
+        Request::builder()
+            .method(http::Method::POST)
+            .uri(uri)
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_new_message() {
+        let app = build_app(test_pool().await);
+
+        let response = app
+            .clone()
+            .oneshot(post_json("/", serde_json::json!({ "message": "Hello Cluster!" })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let created: HelloJson = serde_json::from_slice(&body).unwrap();
+        assert_eq!(created.message, "Hello Cluster!");
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/one/{}", created.id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let fetched: HelloJson = serde_json::from_slice(&body).unwrap();
+        assert_eq!(fetched.id, created.id);
+        assert_eq!(fetched.message, "Hello Cluster!");
+    }
+
+    #[tokio::test]
+    async fn test_new_message_rejects_empty() {
+        let app = build_app(test_pool().await);
+        let response = app
+            .oneshot(post_json("/", serde_json::json!({ "message": "  " })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json, serde_json::json!({ "error": "Message can't be empty", "code": 422 }));
+    }
+
+    #[tokio::test]
+    async fn test_etag() {
+        let app = build_app(test_pool().await);
+
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri("/one/1").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = response.headers().get(header::ETAG).unwrap().clone();
+        assert!(etag.to_str().unwrap().starts_with("W/\""));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/one/1")
+                    .header(header::IF_NONE_MATCH, etag.clone())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers().get(header::ETAG), Some(&etag));
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert!(body.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_etag_changes_with_message() {
+        let app = build_app(test_pool().await);
+        let first = app
+            .clone()
+            .oneshot(Request::builder().uri("/one/1").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let etag = first.headers().get(header::ETAG).unwrap().clone();
+
+        app.clone()
+            .oneshot(post_json("/one/1", serde_json::json!({ "message": "Changed" })))
+            .await
+            .unwrap();
+
+        // A stale ETag gets the full, new message back
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/one/1")
+                    .header(header::IF_NONE_MATCH, etag.clone())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_ne!(response.headers().get(header::ETAG), Some(&etag));
+        assert_eq!(into_message(response).await.message, "Changed");
+    }
+
+    #[test]
+    fn test_etag_is_stable() {
+        // Pinned, so a change to the hash shows up here rather than as a
+        // wave of cache misses
+        let message = HelloJson { id: 1, message: "Hello".to_string() };
+        assert_eq!(etag_for(&message), "W/\"efc4e51f451e7df4\"");
+    }
+
+    #[test]
+    fn test_etag_matches() {
+        assert!(etag_matches("W/\"abc\"", "W/\"abc\""));
+        assert!(etag_matches("\"abc\"", "W/\"abc\""));
+        assert!(etag_matches("\"xyz\", W/\"abc\"", "W/\"abc\""));
+        assert!(etag_matches("*", "W/\"abc\""));
+        assert!(!etag_matches("W/\"xyz\"", "W/\"abc\""));
+    }
+
+    #[tokio::test]
+    async fn test_database_failure_is_json_error() {
+        let pool = test_pool().await;
+        let app = build_app(pool.clone());
+        // Every query fails once the pool is closed
+        pool.close().await;
+
+        for uri in ["/", "/one/1"] {
+            let response = app
+                .clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(json, serde_json::json!({ "error": "Database error", "code": 500 }));
         }
+
+        let response = app
+            .oneshot(post_json("/", serde_json::json!({ "message": "Hello?" })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json, serde_json::json!({ "error": "Database error", "code": 500 }));
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_missing_message_is_json_error() {
+        let app = build_app(test_pool().await);
+        let response = app
+            .oneshot(Request::builder().uri("/one/99999").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["code"], 404);
+        assert_eq!(json["error"], "No message with id 99999");
+    }
+}
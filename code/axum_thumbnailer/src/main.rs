@@ -0,0 +1,150 @@
+use axum::{
+    body::Bytes,
+    extract::{DefaultBodyLimit, Query},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::post,
+    Router,
+};
+use std::net::SocketAddr;
+use thumbnailer::{web_thumbnail, ThumbnailFormat, DEFAULT_SIZE};
+
+/// Largest image we'll accept as an upload.
+const MAX_UPLOAD_BYTES: usize = 20 * 1024 * 1024;
+
+/// Largest thumbnail width or height a client can ask for.
+const MAX_DIMENSION: u32 = 2000;
+
+#[tokio::main]
+async fn main() {
+    let app = build_app();
+    let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
+    axum::Server::bind(&addr)
+        .serve(app.into_make_service())
+        .await
+        .unwrap();
+}
+
+fn build_app() -> Router {
+    Router::new()
+        .route("/thumbnail", post(thumbnail))
+        .layer(DefaultBodyLimit::max(MAX_UPLOAD_BYTES))
+}
+
+#[derive(serde::Deserialize)]
+struct ThumbnailParams {
+    /// Maximum width, defaulting to `DEFAULT_SIZE`
+    w: Option<u32>,
+    /// Maximum height, defaulting to `DEFAULT_SIZE`
+    h: Option<u32>,
+    /// One of png, jpeg, webp or avif. Defaults to the upload's own format.
+    format: Option<String>,
+}
+
+/// Reads the `format` parameter, rejecting formats this build can't encode
+/// (such as AVIF without the `avif` feature) as a bad request.
+fn parse_format(format: &str) -> Result<ThumbnailFormat, (StatusCode, String)> {
+    let parsed = match format.to_ascii_lowercase().as_str() {
+        "png" => ThumbnailFormat::Png,
+        "jpeg" | "jpg" => ThumbnailFormat::Jpeg,
+        "webp" => ThumbnailFormat::WebP,
+        "avif" => ThumbnailFormat::Avif,
+        _ => return Err((StatusCode::BAD_REQUEST, format!("Unknown thumbnail format: {format}"))),
+    };
+    if !parsed.is_available() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Thumbnail format {format} isn't supported by this server"),
+        ));
+    }
+    Ok(parsed)
+}
+
+/// `POST /thumbnail?w=&h=&format=`, with the image as the request body.
+/// Responds with the thumbnail and its content type.
+async fn thumbnail(
+    Query(params): Query<ThumbnailParams>,
+    body: Bytes,
+) -> Result<Response, (StatusCode, String)> {
+    let width = params.w.unwrap_or(DEFAULT_SIZE);
+    let height = params.h.unwrap_or(DEFAULT_SIZE);
+    if !(1..=MAX_DIMENSION).contains(&width) || !(1..=MAX_DIMENSION).contains(&height) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Width and height must be between 1 and {MAX_DIMENSION}"),
+        ));
+    }
+    let format = params.format.as_deref().map(parse_format).transpose()?;
+
+    // Decoding and resizing are CPU-bound, so keep them off the async workers
+    let result = tokio::task::spawn_blocking(move || web_thumbnail(&body, width, height, format))
+        .await
+        .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+    let (thumbnail, format) =
+        result.map_err(|err| (StatusCode::UNPROCESSABLE_ENTITY, format!("{err:#}")))?;
+    Ok(([(header::CONTENT_TYPE, format.mime_type())], thumbnail).into_response())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use axum::{body::Body, http::Request};
+    use image::{ImageFormat, Rgb, RgbImage};
+    use std::io::Cursor;
+    use tower::ServiceExt;
+
+    fn png_bytes(width: u32, height: u32) -> Vec<u8> {
+        let mut png = Vec::new();
+        RgbImage::from_fn(width, height, |x, y| Rgb([x as u8, y as u8, 0]))
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+        png
+    }
+
+    async fn post_image(uri: &str, body: Vec<u8>) -> Response {
+        build_app()
+            .oneshot(Request::post(uri).body(Body::from(body)).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_thumbnail() {
+        let response = post_image("/thumbnail?w=40&h=40", png_bytes(200, 100)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "image/png");
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let thumbnail = image::load_from_memory_with_format(&body, ImageFormat::Png).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (40, 20));
+    }
+
+    #[tokio::test]
+    async fn test_thumbnail_format() {
+        let response = post_image("/thumbnail?format=jpeg", png_bytes(300, 300)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "image/jpeg");
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let thumbnail = image::load_from_memory_with_format(&body, ImageFormat::Jpeg).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (DEFAULT_SIZE, DEFAULT_SIZE));
+    }
+
+    #[tokio::test]
+    async fn test_bad_requests() {
+        let response = post_image("/thumbnail", b"not an image".to_vec()).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let response = post_image("/thumbnail?w=0", png_bytes(10, 10)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = post_image("/thumbnail?format=bmp", png_bytes(10, 10)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // AVIF needs thumbnailer's `avif` feature, which is off by default
+        if !ThumbnailFormat::Avif.is_available() {
+            let response = post_image("/thumbnail?format=avif", png_bytes(10, 10)).await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            assert!(String::from_utf8_lossy(&body).contains("avif"));
+        }
+    }
+}
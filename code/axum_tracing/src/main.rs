@@ -1,37 +1,24 @@
-use axum::{routing::get, Router};
+use axum::{
+    body::Body,
+    http::{Request, Response},
+    middleware,
+    routing::get,
+    Router,
+};
 use std::net::SocketAddr;
+use std::time::Duration;
+use tower_http::trace::TraceLayer;
+use tracing::{field, Span};
+
+mod request_id;
 
 #[tokio::main]
 async fn main() {
-    // Tracing
-    use tracing_subscriber::fmt::format::FmtSpan;
-
-    let subscriber = tracing_subscriber::fmt()
-        // Use a more compact, abbreviated log format
-        .compact()
-        // Display source code file paths
-        .with_file(true)
-        // Display source code line numbers
-        .with_line_number(true)
-        // Display the thread ID an event was recorded on
-        .with_thread_ids(true)
-        // Don't display the event's target (module path)
-        .with_target(false)
-        // Add span events
-        .with_span_events(FmtSpan::ENTER | FmtSpan::CLOSE)
-        // Display debug-level info
-        .with_max_level(tracing_subscriber::filter::LevelFilter::INFO)
-        // Build the subscriber
-        .finish();
-
-    tracing::subscriber::set_global_default(subscriber).unwrap();
+    // Tracing: set RUST_LOG to change the verbosity, e.g. RUST_LOG=axum_tracing=debug,info
+    let _guard = tokio_tracing::init_tracing(tokio_tracing::TracingOptions::default()).unwrap();
 
     // Axum App
-    use tower_http::trace::{self, TraceLayer};
-    let app = Router::new().route("/", get(say_hello_text)).layer(
-        TraceLayer::new_for_http()
-            .on_response(trace::DefaultOnResponse::new().level(tracing::Level::INFO)),
-    );
+    let app = build_app();
     let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
     axum::Server::bind(&addr)
         .serve(app.into_make_service())
@@ -39,6 +26,120 @@ async fn main() {
         .unwrap();
 }
 
+fn build_app() -> Router {
+    Router::new()
+        .route("/", get(say_hello_text))
+        .layer(
+            TraceLayer::new_for_http()
+                .make_span_with(make_span)
+                .on_response(record_latency),
+        )
+        // Outermost, so the trace layer's spans sit inside the request id span
+        .layer(middleware::from_fn(request_id::propagate_request_id))
+}
+
+/// A span per request, with empty fields filled in by [`record_latency`].
+fn make_span(request: &Request<Body>) -> Span {
+    tracing::info_span!(
+        "http",
+        method = %request.method(),
+        uri = %request.uri(),
+        status = field::Empty,
+        latency_ms = field::Empty,
+    )
+}
+
+/// Records the status and latency (in whole milliseconds) as structured
+/// fields, on the span and on a "finished" event, so log collectors can
+/// query them without parsing the message.
+fn record_latency<B>(response: &Response<B>, latency: Duration, span: &Span) {
+    let latency_ms = latency.as_millis() as u64;
+    let status = response.status().as_u16();
+    span.record("status", status);
+    span.record("latency_ms", latency_ms);
+    tracing::info!(status, latency_ms, "finished processing request");
+}
+
 async fn say_hello_text() -> &'static str {
     "Hello, world!"
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use axum::http::StatusCode;
+    use std::sync::{Arc, Mutex};
+    use request_id::X_REQUEST_ID;
+    use tower::ServiceExt;
+
+    /// Collects everything a test subscriber writes.
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_records_latency() {
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_ansi(false)
+            .with_writer(move || writer.clone())
+            .finish();
+        let _default = tracing::subscriber::set_default(subscriber);
+
+        let response = build_app()
+            .oneshot(Request::get("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(&body[..], b"Hello, world!");
+
+        let logs = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        assert!(logs.contains("finished processing request"), "{logs}");
+        assert!(logs.contains("latency_ms="), "{logs}");
+        assert!(logs.contains("status=200"), "{logs}");
+    }
+
+    #[tokio::test]
+    async fn test_generates_request_id() {
+        let response = build_app()
+            .oneshot(Request::get("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let id = response.headers()[&X_REQUEST_ID].to_str().unwrap();
+        assert!(uuid::Uuid::parse_str(id).is_ok(), "{id}");
+    }
+
+    #[tokio::test]
+    async fn test_preserves_request_id() {
+        let request = Request::get("/")
+            .header(&X_REQUEST_ID, "upstream-1234")
+            .body(Body::empty())
+            .unwrap();
+        let response = build_app().oneshot(request).await.unwrap();
+        assert_eq!(response.headers()[&X_REQUEST_ID], "upstream-1234");
+    }
+
+    #[tokio::test]
+    async fn test_replaces_oversized_request_id() {
+        let request = Request::get("/")
+            .header(&X_REQUEST_ID, "x".repeat(500))
+            .body(Body::empty())
+            .unwrap();
+        let response = build_app().oneshot(request).await.unwrap();
+        let id = response.headers()[&X_REQUEST_ID].to_str().unwrap();
+        assert!(uuid::Uuid::parse_str(id).is_ok(), "{id}");
+    }
+}
@@ -0,0 +1,36 @@
+use axum::{
+    http::{HeaderName, HeaderValue, Request},
+    middleware::Next,
+    response::Response,
+};
+use tracing::Instrument;
+use uuid::Uuid;
+
+pub static X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest incoming id we'll reuse; anything longer gets a fresh one.
+const MAX_ID_LEN: usize = 128;
+
+/// Middleware giving every request a correlation id.
+///
+/// The id comes from the incoming `X-Request-Id` header, or is a new UUID if
+/// there isn't a usable one. It's recorded on a `request` span wrapping the
+/// rest of the request, so every log line carries it, and echoed back in
+/// the response's `X-Request-Id` header.
+pub async fn propagate_request_id<B>(request: Request<B>, next: Next<B>) -> Response {
+    let id = request
+        .headers()
+        .get(&X_REQUEST_ID)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| !id.is_empty() && id.len() <= MAX_ID_LEN)
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    let span = tracing::info_span!("request", request_id = %id);
+    let mut response = next.run(request).instrument(span).await;
+
+    // Either it came from a valid header, or it's a UUID
+    let value = HeaderValue::from_str(&id).expect("request id is a valid header value");
+    response.headers_mut().insert(X_REQUEST_ID.clone(), value);
+    response
+}
@@ -0,0 +1,168 @@
+//! A builder whose type records whether the required setting has been
+//! given, so a half-configured `ThingConfig` can't be built.
+//!
+//! ```
+//! use builder::ThingConfig;
+//!
+//! let thing = ThingConfig::new()
+//!     .do_a()
+//!     .with_setting(3)
+//!     .build();
+//! thing.run();
+//! ```
+//!
+//! Leaving out `with_setting` is caught by the compiler:
+//!
+//! ```compile_fail
+//! use builder::ThingConfig;
+//!
+//! let thing = ThingConfig::new()
+//!     .do_a()
+//!     .build();
+//! ```
+
+use std::marker::PhantomData;
+
+/// Marks a `ThingConfig` that doesn't have its setting yet.
+pub struct SettingUnset;
+
+/// Marks a `ThingConfig` whose setting has been given.
+pub struct SettingSet;
+
+pub struct ThingConfig<State = SettingUnset> {
+    do_a: bool,
+    do_b: bool,
+    setting: usize,
+    another_setting: usize,
+    state: PhantomData<State>,
+}
+
+impl ThingConfig<SettingUnset> {
+    pub fn new() -> Self {
+        ThingConfig {
+            do_a: false,
+            do_b: false,
+            setting: 0,
+            another_setting: 0,
+            state: PhantomData,
+        }
+    }
+}
+
+impl Default for ThingConfig<SettingUnset> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<State> ThingConfig<State> {
+    pub fn do_a(mut self) -> Self {
+        self.do_a = true;
+        self
+    }
+
+    pub fn do_b(mut self) -> Self {
+        self.do_b = true;
+        self
+    }
+
+    pub fn with_setting(self, setting: usize) -> ThingConfig<SettingSet> {
+        ThingConfig {
+            do_a: self.do_a,
+            do_b: self.do_b,
+            setting,
+            another_setting: self.another_setting,
+            state: PhantomData,
+        }
+    }
+
+    pub fn with_another_setting(mut self, setting: usize) -> Self {
+        self.another_setting = setting;
+        self
+    }
+}
+
+impl ThingConfig<SettingSet> {
+    /// Finishes configuration. Only available once `with_setting` has been
+    /// called.
+    pub fn build(self) -> Thing {
+        Thing {
+            do_a: self.do_a,
+            do_b: self.do_b,
+            setting: self.setting,
+            another_setting: self.another_setting,
+        }
+    }
+}
+
+/// A fully configured thing. It can't be changed once built, and can be run
+/// as many times as you like.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Thing {
+    do_a: bool,
+    do_b: bool,
+    setting: usize,
+    another_setting: usize,
+}
+
+impl Thing {
+    pub fn run(&self) {
+        for line in self.report() {
+            println!("{line}");
+        }
+    }
+
+    /// What `run` prints.
+    fn report(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if self.do_a {
+            lines.push("Doing A".to_string());
+        }
+        if self.do_b {
+            lines.push("Doing B".to_string());
+        }
+        lines.push(format!("Setting: {}", self.setting));
+        lines.push(format!("Another Setting: {}", self.another_setting));
+        lines
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_complete_chain() {
+        let thing = ThingConfig::new()
+            .do_b()
+            .with_another_setting(7)
+            .with_setting(3)
+            .do_a()
+            .build();
+        assert_eq!(
+            thing,
+            Thing {
+                do_a: true,
+                do_b: true,
+                setting: 3,
+                another_setting: 7,
+            }
+        );
+    }
+
+    #[test]
+    fn test_run_twice() {
+        let thing = ThingConfig::new().do_a().with_setting(3).build();
+        let expected = ["Doing A", "Setting: 3", "Another Setting: 0"];
+        assert_eq!(thing.report(), expected);
+        thing.run();
+        thing.run();
+        assert_eq!(thing.report(), expected);
+    }
+
+    #[test]
+    fn test_setting_can_be_changed() {
+        let thing = ThingConfig::new().with_setting(1).with_setting(2).build();
+        assert_eq!(thing.setting, 2);
+    }
+}
@@ -1,13 +1,32 @@
 use thiserror::Error;
 
-#[derive(Error, Debug)]
+#[derive(Error, Debug, PartialEq, Eq)]
 enum ThingError {
     #[error("Setting must be between 0 and 10")]
     SettingOutOfRange,
+    #[error("Another setting must be between 0 and 100")]
+    AnotherSettingOutOfRange,
 }
 
 type ThingResult<T> = Result<T, ThingError>;
 
+fn validate_setting(setting: usize) -> ThingResult<usize> {
+    if setting > 10 {
+        Err(ThingError::SettingOutOfRange)
+    } else {
+        Ok(setting)
+    }
+}
+
+fn validate_another_setting(setting: usize) -> ThingResult<usize> {
+    if setting > 100 {
+        Err(ThingError::AnotherSettingOutOfRange)
+    } else {
+        Ok(setting)
+    }
+}
+
+#[derive(Debug)]
 struct ThingConfig {
     do_a: bool,
     do_b: bool,
@@ -37,16 +56,12 @@ impl ThingConfig {
     }
 
     fn with_setting(mut self, setting: usize) -> ThingResult<Self> {
-        if setting > 10 {
-            Err(ThingError::SettingOutOfRange)
-        } else {
-            self.setting = setting;
-            Ok(self)
-        }
+        self.setting = validate_setting(setting)?;
+        Ok(self)
     }
 
     fn with_another_setting(mut self, setting: usize) -> ThingResult<Self> {
-        self.another_setting = setting;
+        self.another_setting = validate_another_setting(setting)?;
         Ok(self)
     }
 
@@ -63,11 +78,201 @@ impl ThingConfig {
     }
 }
 
+impl Default for ThingConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The raw values for a `ThingConfig`, e.g. as read from a config file.
+/// Converting to a `ThingConfig` validates everything at once.
+#[derive(Debug, Default)]
+struct ThingConfigParams {
+    do_a: bool,
+    do_b: bool,
+    setting: usize,
+    another_setting: usize,
+}
+
+impl TryFrom<ThingConfigParams> for ThingConfig {
+    type Error = Vec<ThingError>;
+
+    fn try_from(params: ThingConfigParams) -> Result<Self, Self::Error> {
+        let mut builder = ThingConfigBuilder::new()
+            .with_setting(params.setting)
+            .with_another_setting(params.another_setting);
+        if params.do_a {
+            builder = builder.do_a();
+        }
+        if params.do_b {
+            builder = builder.do_b();
+        }
+        builder.build()
+    }
+}
+
+/// Builds a `ThingConfig` without stopping at the first bad value: every
+/// validation failure is kept, and `build` reports them all together.
+struct ThingConfigBuilder {
+    config: ThingConfig,
+    errors: Vec<ThingError>,
+}
+
+#[allow(dead_code)]
+impl ThingConfigBuilder {
+    fn new() -> Self {
+        ThingConfigBuilder {
+            config: ThingConfig::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    fn do_a(mut self) -> Self {
+        self.config.do_a = true;
+        self
+    }
+
+    fn do_b(mut self) -> Self {
+        self.config.do_b = true;
+        self
+    }
+
+    fn with_setting(mut self, setting: usize) -> Self {
+        match validate_setting(setting) {
+            Ok(setting) => self.config.setting = setting,
+            Err(err) => self.errors.push(err),
+        }
+        self
+    }
+
+    fn with_another_setting(mut self, setting: usize) -> Self {
+        match validate_another_setting(setting) {
+            Ok(setting) => self.config.another_setting = setting,
+            Err(err) => self.errors.push(err),
+        }
+        self
+    }
+
+    fn build(self) -> Result<ThingConfig, Vec<ThingError>> {
+        if self.errors.is_empty() {
+            Ok(self.config)
+        } else {
+            Err(self.errors)
+        }
+    }
+}
+
 fn main() -> ThingResult<()> {
     ThingConfig::new()
         .do_a()?
         .with_setting(3)?
         .execute()?;
 
+    let result = ThingConfigBuilder::new()
+        .with_setting(11)
+        .with_another_setting(420)
+        .build();
+    if let Err(errors) = result {
+        for err in errors {
+            println!("Invalid config: {err}");
+        }
+    }
+
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_builder_valid() {
+        let config = ThingConfigBuilder::new()
+            .do_a()
+            .with_setting(3)
+            .with_another_setting(7)
+            .build()
+            .unwrap();
+        assert!(config.do_a);
+        assert!(!config.do_b);
+        assert_eq!(config.setting, 3);
+        assert_eq!(config.another_setting, 7);
+    }
+
+    #[test]
+    fn test_builder_reports_every_error() {
+        let errors = ThingConfigBuilder::new()
+            .with_setting(11)
+            .do_a()
+            .with_another_setting(101)
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            errors,
+            [ThingError::SettingOutOfRange, ThingError::AnotherSettingOutOfRange]
+        );
+    }
+
+    #[test]
+    fn test_another_setting_bounds() {
+        let config = ThingConfig::new().with_another_setting(0).unwrap();
+        assert_eq!(config.another_setting, 0);
+        let config = ThingConfig::new().with_another_setting(50).unwrap();
+        assert_eq!(config.another_setting, 50);
+        let config = ThingConfig::new().with_another_setting(100).unwrap();
+        assert_eq!(config.another_setting, 100);
+        assert!(matches!(
+            ThingConfig::new().with_another_setting(101),
+            Err(ThingError::AnotherSettingOutOfRange)
+        ));
+    }
+
+    #[test]
+    fn test_error_messages_name_the_setting() {
+        assert!(ThingError::SettingOutOfRange.to_string().starts_with("Setting"));
+        assert!(ThingError::AnotherSettingOutOfRange
+            .to_string()
+            .starts_with("Another setting"));
+    }
+
+    #[test]
+    fn test_default() {
+        let config = ThingConfig::default();
+        assert!(!config.do_a && !config.do_b);
+        assert_eq!((config.setting, config.another_setting), (0, 0));
+    }
+
+    #[test]
+    fn test_from_valid_params() {
+        let params = ThingConfigParams {
+            do_b: true,
+            setting: 10,
+            another_setting: 100,
+            ..Default::default()
+        };
+        let config = ThingConfig::try_from(params).unwrap();
+        assert!(!config.do_a && config.do_b);
+        assert_eq!((config.setting, config.another_setting), (10, 100));
+    }
+
+    #[test]
+    fn test_from_invalid_params() {
+        let params = ThingConfigParams {
+            setting: 11,
+            another_setting: 101,
+            ..Default::default()
+        };
+        assert_eq!(
+            ThingConfig::try_from(params).unwrap_err(),
+            [ThingError::SettingOutOfRange, ThingError::AnotherSettingOutOfRange]
+        );
+    }
+
+    #[test]
+    fn test_short_circuit() {
+        assert!(matches!(
+            ThingConfig::new().with_setting(11),
+            Err(ThingError::SettingOutOfRange)
+        ));
+    }
+}
@@ -0,0 +1,716 @@
+/// A size in bytes. Compares equal to, and orders against, sizes in any of
+/// the other units by converting them to bytes first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Bytes(pub usize);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Kilobytes(pub usize);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MegaBytes(pub usize);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Gigabytes(pub usize);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Terabytes(pub usize);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Petabytes(pub usize);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DecimalKilobytes(pub usize);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DecimalMegabytes(pub usize);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DecimalGigabytes(pub usize);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DecimalTerabytes(pub usize);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DecimalPetabytes(pub usize);
+
+#[cfg(feature = "serde")]
+mod serde_impl;
+
+/// Which family of multipliers to use when formatting a [`Bytes`] value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnitSystem {
+    /// Base-1024 units: KiB, MiB, GiB, TiB, PiB
+    Binary,
+    /// Base-1000 (SI) units: kB, MB, GB, TB, PB
+    Decimal,
+}
+
+const KB: usize = 1024;
+const MB: usize = KB * 1024;
+const GB: usize = MB * 1024;
+// A terabyte doesn't fit in a 32-bit `usize`, so the larger units are `u64`
+const TB: u64 = GB as u64 * 1024;
+const PB: u64 = TB * 1024;
+
+const DECIMAL_KB: usize = 1000;
+const DECIMAL_MB: usize = DECIMAL_KB * 1000;
+const DECIMAL_GB: usize = DECIMAL_MB * 1000;
+const DECIMAL_TB: u64 = DECIMAL_GB as u64 * 1000;
+const DECIMAL_PB: u64 = DECIMAL_TB * 1000;
+
+/// Multiplies `count` units of `unit` bytes, saturating at `usize::MAX`
+/// rather than overflowing. On 32-bit targets that's only 4 GB.
+fn units_to_bytes(count: usize, unit: u64) -> usize {
+    (count as u64)
+        .checked_mul(unit)
+        .and_then(|bytes| usize::try_from(bytes).ok())
+        .unwrap_or(usize::MAX)
+}
+
+// Converting to `Bytes` saturates at `usize::MAX` instead of overflowing.
+
+impl From<Kilobytes> for Bytes {
+    fn from(kb: Kilobytes) -> Self {
+        Self(kb.0.saturating_mul(KB))
+    }
+}
+
+impl From<MegaBytes> for Bytes {
+    fn from(mb: MegaBytes) -> Self {
+        Self(mb.0.saturating_mul(MB))
+    }
+}
+
+impl From<Gigabytes> for Bytes {
+    fn from(gb: Gigabytes) -> Self {
+        Self(gb.0.saturating_mul(GB))
+    }
+}
+
+impl From<Terabytes> for Bytes {
+    fn from(tb: Terabytes) -> Self {
+        Self(units_to_bytes(tb.0, TB))
+    }
+}
+
+impl From<Petabytes> for Bytes {
+    fn from(pb: Petabytes) -> Self {
+        Self(units_to_bytes(pb.0, PB))
+    }
+}
+
+impl From<DecimalKilobytes> for Bytes {
+    fn from(kb: DecimalKilobytes) -> Self {
+        Self(kb.0.saturating_mul(DECIMAL_KB))
+    }
+}
+
+impl From<DecimalMegabytes> for Bytes {
+    fn from(mb: DecimalMegabytes) -> Self {
+        Self(mb.0.saturating_mul(DECIMAL_MB))
+    }
+}
+
+impl From<DecimalGigabytes> for Bytes {
+    fn from(gb: DecimalGigabytes) -> Self {
+        Self(gb.0.saturating_mul(DECIMAL_GB))
+    }
+}
+
+impl From<DecimalTerabytes> for Bytes {
+    fn from(tb: DecimalTerabytes) -> Self {
+        Self(units_to_bytes(tb.0, DECIMAL_TB))
+    }
+}
+
+impl From<DecimalPetabytes> for Bytes {
+    fn from(pb: DecimalPetabytes) -> Self {
+        Self(units_to_bytes(pb.0, DECIMAL_PB))
+    }
+}
+
+/// Converts to whole kilobytes, discarding any remainder: `Bytes(1535)`
+/// becomes `Kilobytes(1)`. Use [`Kilobytes::try_from_exact`] to reject
+/// values that aren't a whole number of kilobytes.
+impl From<Bytes> for Kilobytes {
+    fn from(bytes: Bytes) -> Self {
+        Self(bytes.0 / KB)
+    }
+}
+
+/// Converts to whole megabytes, discarding any remainder. Use
+/// [`MegaBytes::try_from_exact`] to reject values that aren't a whole number
+/// of megabytes.
+impl From<Bytes> for MegaBytes {
+    fn from(bytes: Bytes) -> Self {
+        Self(bytes.0 / MB)
+    }
+}
+
+/// Error returned when a [`Bytes`] value isn't a whole number of the
+/// requested unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InexactConversionError {
+    /// The bytes that would have been lost to truncation.
+    pub remainder: usize,
+}
+
+impl std::fmt::Display for InexactConversionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "not a whole number of units ({} bytes left over)", self.remainder)
+    }
+}
+
+impl std::error::Error for InexactConversionError {}
+
+// These can't be `TryFrom<Bytes>`: the `From` impls above already give
+// `Kilobytes` and `MegaBytes` an infallible `TryFrom` via the blanket impl.
+impl Kilobytes {
+    /// Converts to kilobytes, failing if `bytes` isn't an exact multiple of
+    /// 1024.
+    ///
+    /// ```
+    /// use bytes::{Bytes, Kilobytes};
+    /// assert_eq!(Kilobytes::try_from_exact(Bytes(2048)).unwrap().0, 2);
+    /// assert!(Kilobytes::try_from_exact(Bytes(2049)).is_err());
+    /// ```
+    pub fn try_from_exact(bytes: Bytes) -> Result<Self, InexactConversionError> {
+        match bytes.0 % KB {
+            0 => Ok(Self(bytes.0 / KB)),
+            remainder => Err(InexactConversionError { remainder }),
+        }
+    }
+}
+
+impl MegaBytes {
+    /// Converts to megabytes, failing if `bytes` isn't an exact multiple of
+    /// 1024 * 1024.
+    pub fn try_from_exact(bytes: Bytes) -> Result<Self, InexactConversionError> {
+        match bytes.0 % MB {
+            0 => Ok(Self(bytes.0 / MB)),
+            remainder => Err(InexactConversionError { remainder }),
+        }
+    }
+}
+
+/// Implements `PartialEq` and `PartialOrd` in both directions between every
+/// pair of the listed types, by converting both sides to [`Bytes`].
+macro_rules! cross_compare {
+    (@pair $a:ident, $b:ident) => {
+        impl PartialEq<$b> for $a {
+            fn eq(&self, other: &$b) -> bool {
+                Bytes::from(*self) == Bytes::from(*other)
+            }
+        }
+
+        impl PartialOrd<$b> for $a {
+            fn partial_cmp(&self, other: &$b) -> Option<std::cmp::Ordering> {
+                Bytes::from(*self).partial_cmp(&Bytes::from(*other))
+            }
+        }
+    };
+    () => {};
+    ($first:ident $(, $rest:ident)*) => {
+        $(
+            cross_compare!(@pair $first, $rest);
+            cross_compare!(@pair $rest, $first);
+        )*
+        cross_compare!($($rest),*);
+    };
+}
+
+cross_compare!(
+    Bytes,
+    Kilobytes,
+    MegaBytes,
+    Gigabytes,
+    Terabytes,
+    Petabytes,
+    DecimalKilobytes,
+    DecimalMegabytes,
+    DecimalGigabytes,
+    DecimalTerabytes,
+    DecimalPetabytes
+);
+
+/// Units used by `Display` and `format_precise`, largest first.
+const UNITS: [(u64, &str); 5] = [(PB, "PB"), (TB, "TB"), (GB as u64, "GB"), (MB as u64, "MB"), (KB as u64, "KB")];
+
+/// IEC binary units, largest first.
+const BINARY_UNITS: [(u64, &str); 5] = [
+    (PB, "PiB"),
+    (TB, "TiB"),
+    (GB as u64, "GiB"),
+    (MB as u64, "MiB"),
+    (KB as u64, "KiB"),
+];
+
+/// SI decimal units, largest first.
+const DECIMAL_UNITS: [(u64, &str); 5] = [
+    (DECIMAL_PB, "PB"),
+    (DECIMAL_TB, "TB"),
+    (DECIMAL_GB as u64, "GB"),
+    (DECIMAL_MB as u64, "MB"),
+    (DECIMAL_KB as u64, "kB"),
+];
+
+impl Bytes {
+    /// One kilobyte (1024 bytes).
+    pub const KB: Bytes = Bytes(KB);
+    /// One megabyte (1024 kilobytes).
+    pub const MB: Bytes = Bytes(MB);
+    /// One gigabyte (1024 megabytes).
+    pub const GB: Bytes = Bytes(GB);
+
+    /// Creates a size of `n` bytes. Usable in `const` items:
+    ///
+    /// ```
+    /// use bytes::Bytes;
+    /// const HEADER: Bytes = Bytes::new(512);
+    /// const LIMIT: Bytes = Bytes::MB;
+    /// assert!(HEADER < LIMIT);
+    /// ```
+    pub const fn new(n: usize) -> Bytes {
+        Bytes(n)
+    }
+}
+
+impl Bytes {
+    /// Formats the value in the largest unit that yields at least one whole
+    /// unit, with `decimals` digits after the decimal point. Extra digits are
+    /// truncated rather than rounded, so `format_precise(0)` never rounds up
+    /// into the next unit.
+    ///
+    /// ```
+    /// use bytes::Bytes;
+    /// assert_eq!(Bytes(1536).format_precise(2), "1.50 KB");
+    /// ```
+    pub fn format_precise(&self, decimals: usize) -> String {
+        self.format_units(&UNITS, decimals)
+    }
+
+    /// Formats the value as a whole number of the largest fitting unit, using
+    /// either binary (KiB, MiB, ...) or decimal (kB, MB, ...) units.
+    ///
+    /// ```
+    /// use bytes::{Bytes, UnitSystem};
+    /// assert_eq!(Bytes(1_000_000).format_with(UnitSystem::Decimal), "1 MB");
+    /// assert_eq!(Bytes(1_000_000).format_with(UnitSystem::Binary), "976 KiB");
+    /// ```
+    pub fn format_with(&self, system: UnitSystem) -> String {
+        match system {
+            UnitSystem::Binary => self.format_units(&BINARY_UNITS, 0),
+            UnitSystem::Decimal => self.format_units(&DECIMAL_UNITS, 0),
+        }
+    }
+
+    fn format_units(&self, units: &[(u64, &str)], decimals: usize) -> String {
+        let bytes = self.0 as u64;
+        let Some((divisor, unit)) = units.iter().find(|(divisor, _)| bytes >= *divisor) else {
+            return format!("{bytes} B");
+        };
+        let value = bytes as f64 / *divisor as f64;
+        let scale = 10f64.powi(decimals as i32);
+        let truncated = (value * scale).floor() / scale;
+        format!("{truncated:.decimals$} {unit}")
+    }
+}
+
+impl std::fmt::Display for Bytes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.format_precise(0))
+    }
+}
+
+impl Bytes {
+    /// Subtracts `other`, returning `None` instead of saturating if the
+    /// result would be negative.
+    pub fn checked_sub(self, other: Bytes) -> Option<Bytes> {
+        self.0.checked_sub(other.0).map(Bytes)
+    }
+
+    /// Adds `other`, returning `None` instead of saturating if the result
+    /// would overflow.
+    pub fn checked_add(self, other: Bytes) -> Option<Bytes> {
+        self.0.checked_add(other.0).map(Bytes)
+    }
+
+    /// Multiplies by `factor`, returning `None` instead of saturating if the
+    /// result would overflow.
+    pub fn checked_mul(self, factor: usize) -> Option<Bytes> {
+        self.0.checked_mul(factor).map(Bytes)
+    }
+}
+
+/// Addition saturates at `usize::MAX` rather than overflowing. Use
+/// [`Bytes::checked_add`] to detect that case.
+impl std::ops::Add for Bytes {
+    type Output = Bytes;
+
+    fn add(self, rhs: Bytes) -> Bytes {
+        Bytes(self.0.saturating_add(rhs.0))
+    }
+}
+
+/// Subtraction saturates at zero rather than underflowing. Use
+/// [`Bytes::checked_sub`] to detect that case.
+impl std::ops::Sub for Bytes {
+    type Output = Bytes;
+
+    fn sub(self, rhs: Bytes) -> Bytes {
+        Bytes(self.0.saturating_sub(rhs.0))
+    }
+}
+
+impl std::ops::AddAssign for Bytes {
+    fn add_assign(&mut self, rhs: Bytes) {
+        *self = *self + rhs;
+    }
+}
+
+impl std::ops::SubAssign for Bytes {
+    fn sub_assign(&mut self, rhs: Bytes) {
+        *self = *self - rhs;
+    }
+}
+
+/// Multiplication saturates at `usize::MAX` rather than overflowing. Use
+/// [`Bytes::checked_mul`] to detect that case.
+impl std::ops::Mul<usize> for Bytes {
+    type Output = Bytes;
+
+    fn mul(self, rhs: usize) -> Bytes {
+        Bytes(self.0.saturating_mul(rhs))
+    }
+}
+
+impl std::ops::Div<usize> for Bytes {
+    type Output = Bytes;
+
+    fn div(self, rhs: usize) -> Bytes {
+        Bytes(self.0 / rhs)
+    }
+}
+
+/// Error returned when a string can't be parsed into [`Bytes`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseBytesError {
+    /// The input was empty or only whitespace.
+    Empty,
+    /// The input started with a minus sign.
+    Negative,
+    /// The numeric part was missing or not a whole number.
+    InvalidNumber(String),
+    /// The unit suffix wasn't one of B, KB, MB, GB, TB or PB.
+    UnknownUnit(String),
+    /// The value doesn't fit in a `usize` once converted to bytes.
+    Overflow,
+}
+
+impl std::fmt::Display for ParseBytesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseBytesError::Empty => write!(f, "cannot parse a size from an empty string"),
+            ParseBytesError::Negative => write!(f, "sizes cannot be negative"),
+            ParseBytesError::InvalidNumber(n) => write!(f, "invalid number: '{n}'"),
+            ParseBytesError::UnknownUnit(u) => write!(f, "unknown unit: '{u}'"),
+            ParseBytesError::Overflow => write!(f, "size is too large"),
+        }
+    }
+}
+
+impl std::error::Error for ParseBytesError {}
+
+impl std::str::FromStr for Bytes {
+    type Err = ParseBytesError;
+
+    /// Parses strings such as "8MB", "512 KB" or "100". Units are
+    /// case-insensitive and use the same base-1024 multipliers as `Display`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(ParseBytesError::Empty);
+        }
+        if s.starts_with('-') {
+            return Err(ParseBytesError::Negative);
+        }
+
+        let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (number, unit) = s.split_at(split);
+        if number.is_empty() {
+            return Err(ParseBytesError::InvalidNumber(s.to_string()));
+        }
+        let number: usize = number.parse().map_err(|_| ParseBytesError::Overflow)?;
+
+        let unit = unit.trim_start();
+        let multiplier: u64 = match unit.to_ascii_uppercase().as_str() {
+            "" | "B" => 1,
+            "KB" => KB as u64,
+            "MB" => MB as u64,
+            "GB" => GB as u64,
+            "TB" => TB,
+            "PB" => PB,
+            _ => return Err(ParseBytesError::UnknownUnit(unit.to_string())),
+        };
+
+        (number as u64)
+            .checked_mul(multiplier)
+            .and_then(|bytes| usize::try_from(bytes).ok())
+            .map(Bytes)
+            .ok_or(ParseBytesError::Overflow)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bytes() {
+        assert_eq!(Bytes(0).to_string(), "0 B");
+        assert_eq!(Bytes(1023).to_string(), "1023 B");
+        assert_eq!(Bytes(1024).to_string(), "1 KB");
+    }
+
+    #[test]
+    fn test_kilobyte_boundary() {
+        let bytes: Bytes = Kilobytes(1023).into();
+        assert_eq!(bytes.to_string(), "1023 KB");
+        let bytes: Bytes = Kilobytes(1024).into();
+        assert_eq!(bytes.to_string(), "1 MB");
+        assert_eq!(Bytes(1048576).to_string(), "1 MB");
+        assert_eq!(Bytes(1048575).to_string(), "1023 KB");
+    }
+
+    #[test]
+    fn test_megabyte_boundary() {
+        let bytes: Bytes = MegaBytes(1023).into();
+        assert_eq!(bytes.to_string(), "1023 MB");
+        let bytes: Bytes = MegaBytes(1024).into();
+        assert_eq!(bytes.to_string(), "1 GB");
+    }
+
+    #[test]
+    fn test_gigabyte_boundary() {
+        let bytes: Bytes = Gigabytes(1023).into();
+        assert_eq!(bytes.to_string(), "1023 GB");
+        let bytes: Bytes = Gigabytes(1024).into();
+        assert_eq!(bytes.to_string(), "1 TB");
+    }
+
+    #[test]
+    fn test_terabyte_boundary() {
+        let bytes: Bytes = Terabytes(5).into();
+        assert_eq!(bytes.to_string(), "5 TB");
+        let bytes: Bytes = Terabytes(1023).into();
+        assert_eq!(bytes.to_string(), "1023 TB");
+        let bytes: Bytes = Terabytes(1024).into();
+        assert_eq!(bytes.to_string(), "1 PB");
+    }
+
+    #[test]
+    fn test_petabytes() {
+        let bytes: Bytes = Petabytes(3).into();
+        assert_eq!(bytes.to_string(), "3 PB");
+        let bytes: Bytes = Petabytes(2048).into();
+        assert_eq!(bytes.to_string(), "2048 PB");
+    }
+
+    #[test]
+    fn test_conversions_saturate() {
+        assert_eq!(Bytes::from(Kilobytes(usize::MAX)).0, usize::MAX);
+        assert_eq!(Bytes::from(Gigabytes(usize::MAX / 2)).0, usize::MAX);
+        assert_eq!(Bytes::from(Terabytes(usize::MAX)).0, usize::MAX);
+        assert_eq!(Bytes::from(Petabytes(usize::MAX)).0, usize::MAX);
+        assert_eq!(Bytes::from(Petabytes(1)).0 as u64, PB);
+        assert_eq!(Bytes::from(DecimalGigabytes(usize::MAX)).0, usize::MAX);
+        assert_eq!(Bytes::from(DecimalTerabytes(usize::MAX)).0, usize::MAX);
+        assert_eq!(Bytes::from(DecimalPetabytes(usize::MAX)).0, usize::MAX);
+        assert_eq!(Bytes::from(DecimalPetabytes(1)).0 as u64, DECIMAL_PB);
+    }
+
+    #[test]
+    fn test_format_precise() {
+        assert_eq!(Bytes(1536).format_precise(2), "1.50 KB");
+        assert_eq!(Bytes(1536).format_precise(1), "1.5 KB");
+        assert_eq!(Bytes(1536).format_precise(0), "1 KB");
+        assert_eq!(Bytes(1048575).format_precise(0), "1023 KB");
+        assert_eq!(Bytes(512).format_precise(2), "512 B");
+        let bytes: Bytes = MegaBytes(2560).into();
+        assert_eq!(bytes.format_precise(3), "2.500 GB");
+    }
+
+    #[test]
+    fn test_format_with_unit_system() {
+        assert_eq!(Bytes(1_000_000).format_with(UnitSystem::Decimal), "1 MB");
+        assert_eq!(Bytes(1_000_000).format_with(UnitSystem::Binary), "976 KiB");
+        assert_eq!(Bytes(999).format_with(UnitSystem::Decimal), "999 B");
+        assert_eq!(Bytes(1000).format_with(UnitSystem::Decimal), "1 kB");
+        assert_eq!(Bytes(1000).format_with(UnitSystem::Binary), "1000 B");
+        assert_eq!(Bytes(1024).format_with(UnitSystem::Binary), "1 KiB");
+    }
+
+    #[test]
+    fn test_decimal_conversions() {
+        let bytes: Bytes = DecimalKilobytes(1).into();
+        assert_eq!(bytes.0, 1000);
+        let bytes: Bytes = DecimalMegabytes(1).into();
+        assert_eq!(bytes.0, 1_000_000);
+        let bytes: Bytes = DecimalGigabytes(4).into();
+        assert_eq!(bytes.format_with(UnitSystem::Decimal), "4 GB");
+        assert_eq!(bytes.format_with(UnitSystem::Binary), "3 GiB");
+        let bytes: Bytes = DecimalTerabytes(2).into();
+        assert_eq!(bytes.format_with(UnitSystem::Decimal), "2 TB");
+        let bytes: Bytes = DecimalPetabytes(1).into();
+        assert_eq!(bytes.format_with(UnitSystem::Decimal), "1 PB");
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("8MB".parse::<Bytes>().unwrap().0, 8 * MB);
+        assert_eq!("512 KB".parse::<Bytes>().unwrap().0, 512 * KB);
+        assert_eq!("3gb".parse::<Bytes>().unwrap().0, 3 * GB);
+        assert_eq!("  100 b ".parse::<Bytes>().unwrap().0, 100);
+        assert_eq!("100".parse::<Bytes>().unwrap().0, 100);
+        assert_eq!("2 Tb".parse::<Bytes>().unwrap().0 as u64, 2 * TB);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        use std::str::FromStr;
+        assert_eq!(Bytes::from_str("banana").unwrap_err(), ParseBytesError::InvalidNumber("banana".to_string()));
+        assert_eq!(Bytes::from_str("-5 MB").unwrap_err(), ParseBytesError::Negative);
+        assert_eq!(Bytes::from_str("").unwrap_err(), ParseBytesError::Empty);
+        assert_eq!(Bytes::from_str("5 XB").unwrap_err(), ParseBytesError::UnknownUnit("XB".to_string()));
+        assert_eq!(Bytes::from_str("99999999999 PB").unwrap_err(), ParseBytesError::Overflow);
+    }
+
+    #[test]
+    fn test_parse_round_trip() {
+        let sizes: Vec<Bytes> = vec![
+            Bytes(12),
+            Kilobytes(512).into(),
+            MegaBytes(8).into(),
+            Gigabytes(3).into(),
+            Terabytes(5).into(),
+            Petabytes(2).into(),
+        ];
+        for size in sizes {
+            let parsed: Bytes = size.to_string().parse().unwrap();
+            assert_eq!(parsed.0, size.0);
+        }
+    }
+
+    #[test]
+    fn test_add_and_sub() {
+        let total = Bytes::from(MegaBytes(1)) + Bytes::from(Kilobytes(512));
+        assert_eq!(total.0, MB + 512 * KB);
+        assert_eq!((total - Bytes::from(Kilobytes(512))).0, MB);
+
+        let mut running = Bytes(10);
+        running += Bytes(5);
+        assert_eq!(running.0, 15);
+        running -= Bytes(3);
+        assert_eq!(running.0, 12);
+    }
+
+    #[test]
+    fn test_sub_saturates_at_zero() {
+        assert_eq!((Bytes(5) - Bytes(5)).0, 0);
+        assert_eq!((Bytes(5) - Bytes(6)).0, 0);
+        let mut running = Bytes(1);
+        running -= Bytes(100);
+        assert_eq!(running.0, 0);
+    }
+
+    #[test]
+    fn test_checked_sub() {
+        assert_eq!(Bytes(5).checked_sub(Bytes(5)).map(|b| b.0), Some(0));
+        assert_eq!(Bytes(5).checked_sub(Bytes(4)).map(|b| b.0), Some(1));
+        assert!(Bytes(5).checked_sub(Bytes(6)).is_none());
+        assert!(Bytes(0).checked_sub(Bytes(1)).is_none());
+    }
+
+    #[test]
+    fn test_add_and_mul_saturate() {
+        assert_eq!((Bytes(usize::MAX) + Bytes(1)).0, usize::MAX);
+        let mut running = Bytes(usize::MAX - 1);
+        running += Bytes(5);
+        assert_eq!(running.0, usize::MAX);
+        assert_eq!((Bytes(usize::MAX) * 2).0, usize::MAX);
+        assert_eq!((Bytes(usize::MAX / 2 + 1) * 2).0, usize::MAX);
+    }
+
+    #[test]
+    fn test_checked_add_and_mul() {
+        assert_eq!(Bytes(5).checked_add(Bytes(6)).map(|b| b.0), Some(11));
+        assert!(Bytes(usize::MAX).checked_add(Bytes(1)).is_none());
+        assert_eq!(Bytes(5).checked_mul(3).map(|b| b.0), Some(15));
+        assert_eq!(Bytes(usize::MAX).checked_mul(1).map(|b| b.0), Some(usize::MAX));
+        assert!(Bytes(usize::MAX).checked_mul(2).is_none());
+    }
+
+    #[test]
+    fn test_mul_and_div() {
+        assert_eq!((Bytes::from(MegaBytes(2)) * 3).to_string(), "6 MB");
+        assert_eq!((Bytes::from(MegaBytes(3)) / 2).0, 1536 * KB);
+    }
+
+    #[test]
+    fn test_from_bytes_exact() {
+        assert_eq!(Kilobytes::from(Bytes::from(Kilobytes(5))).0, 5);
+        assert_eq!(MegaBytes::from(Bytes::from(MegaBytes(7))).0, 7);
+        assert_eq!(Kilobytes::from(Bytes::from(MegaBytes(1))).0, 1024);
+        assert_eq!(Kilobytes::try_from_exact(Bytes(3 * KB)).unwrap().0, 3);
+        assert_eq!(MegaBytes::try_from_exact(Bytes::from(Kilobytes(2048))).unwrap().0, 2);
+        assert_eq!(MegaBytes::try_from_exact(Bytes(0)).unwrap().0, 0);
+    }
+
+    #[test]
+    fn test_from_bytes_inexact() {
+        // `From` truncates
+        assert_eq!(Kilobytes::from(Bytes(1535)).0, 1);
+        assert_eq!(Kilobytes::from(Bytes(1023)).0, 0);
+        assert_eq!(MegaBytes::from(Bytes(MB + 1)).0, 1);
+        assert_eq!(MegaBytes::from(Bytes::from(Kilobytes(1023))).0, 0);
+
+        // `try_from_exact` reports what would be lost
+        assert_eq!(
+            Kilobytes::try_from_exact(Bytes(1535)).unwrap_err(),
+            InexactConversionError { remainder: 511 }
+        );
+        assert_eq!(
+            MegaBytes::try_from_exact(Bytes::from(Kilobytes(1536))).unwrap_err().remainder,
+            512 * KB
+        );
+    }
+
+    #[test]
+    fn test_cross_unit_equality() {
+        assert_eq!(Kilobytes(1024), MegaBytes(1));
+        assert_eq!(MegaBytes(1), Kilobytes(1024));
+        assert_eq!(Bytes(1024), Kilobytes(1));
+        assert_eq!(Gigabytes(2), MegaBytes(2048));
+        assert_eq!(DecimalKilobytes(1000), DecimalMegabytes(1));
+        assert_eq!(DecimalKilobytes(1), Bytes(1000));
+        assert_ne!(Kilobytes(1000), MegaBytes(1));
+        assert_ne!(DecimalMegabytes(1), MegaBytes(1));
+    }
+
+    #[test]
+    fn test_cross_unit_ordering() {
+        assert!(Kilobytes(1023) < MegaBytes(1));
+        assert!(Kilobytes(1025) > MegaBytes(1));
+        assert!(MegaBytes(1) <= Kilobytes(1024));
+        assert!(DecimalMegabytes(1) < MegaBytes(1));
+        assert!(Terabytes(1) > DecimalTerabytes(1));
+        assert!(Bytes(1) < Kilobytes(1));
+
+        let mut sizes = vec![Bytes::from(MegaBytes(1)), Bytes(10), Bytes::from(Kilobytes(3))];
+        sizes.sort();
+        assert_eq!(sizes, vec![Bytes(10), Bytes(3 * KB), Bytes(MB)]);
+        assert_eq!(sizes.iter().max(), Some(&Bytes(MB)));
+    }
+
+    #[test]
+    fn test_consts() {
+        const LIMIT: Bytes = Bytes::MB;
+        const BUFFER: Bytes = Bytes::new(8 * 1024);
+        assert_eq!(Bytes::MB.0, 1024 * 1024);
+        assert_eq!(LIMIT, MegaBytes(1));
+        assert_eq!(BUFFER, Kilobytes(8));
+        assert_eq!(Bytes::KB, Kilobytes(1));
+        assert_eq!(Bytes::GB, Gigabytes(1));
+        assert_eq!(Bytes::GB.to_string(), "1 GB");
+    }
+}
@@ -1,35 +1,13 @@
-struct Bytes(usize);
-struct Kilobytes(usize);
-struct MegaBytes(usize);
-
-impl From<Kilobytes> for Bytes {
-    fn from(kb: Kilobytes) -> Self {
-        Self(kb.0 * 1024)
-    }
-}
-
-impl From<MegaBytes> for Bytes {
-    fn from(mb: MegaBytes) -> Self {
-        Self(mb.0 * 1024 * 1024)
-    }
-}
-
-impl std::fmt::Display for Bytes {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let bytes = self.0;
-        let kb = bytes / 1024;
-        let mb = kb / 1024;
-        if mb > 0 {
-            write!(f, "{} MB", mb)
-        } else if kb > 0 {
-            write!(f, "{} KB", kb)
-        } else {
-            write!(f, "{} B", bytes)
-        }
-    }
-}
+use bytes::*;
 
 fn main() {
     let bytes: Bytes = MegaBytes(8).into();
     println!("{bytes}");
+    let bytes: Bytes = Terabytes(5).into();
+    println!("{bytes}");
+    println!("{}", Bytes(1536).format_precise(2));
+    let bytes: Bytes = DecimalMegabytes(1).into();
+    println!("{} / {}", bytes.format_with(UnitSystem::Decimal), bytes.format_with(UnitSystem::Binary));
+    let parsed: Bytes = "512 KB".parse().unwrap();
+    println!("Parsed: {parsed}");
 }
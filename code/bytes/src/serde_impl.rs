@@ -0,0 +1,104 @@
+//! `Serialize` and `Deserialize` for [`Bytes`], behind the `serde` feature.
+
+use crate::{Bytes, UNITS};
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+impl Bytes {
+    /// Formats the value in the largest unit it's an exact multiple of, so
+    /// that parsing the result gives back the same number of bytes.
+    fn to_exact_string(self) -> String {
+        let bytes = self.0 as u64;
+        match UNITS.iter().find(|(divisor, _)| bytes >= *divisor && bytes % *divisor == 0) {
+            Some((divisor, unit)) => format!("{} {unit}", bytes / divisor),
+            None => format!("{} B", self.0),
+        }
+    }
+}
+
+/// Serializes as a string such as `"8 MB"`. Sizes that aren't a whole
+/// number of any unit are written in bytes, so nothing is lost.
+impl Serialize for Bytes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_exact_string())
+    }
+}
+
+struct BytesVisitor;
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Bytes;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a size such as \"8 MB\", or a whole number of bytes")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Bytes, E> {
+        value.parse().map_err(E::custom)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Bytes, E> {
+        usize::try_from(value)
+            .map(Bytes)
+            .map_err(|_| E::custom("size is too large"))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Bytes, E> {
+        u64::try_from(value)
+            .map_err(|_| E::custom("sizes cannot be negative"))
+            .and_then(|value| self.visit_u64(value))
+    }
+}
+
+/// Deserializes from either a string accepted by [`str::parse`], or an
+/// integer number of bytes.
+impl<'de> Deserialize<'de> for Bytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(BytesVisitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Kilobytes, MegaBytes, KB, MB};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Config {
+        cache_size: Bytes,
+    }
+
+    #[test]
+    fn test_serialize() {
+        assert_eq!(serde_json::to_string(&Bytes::from(MegaBytes(8))).unwrap(), "\"8 MB\"");
+        assert_eq!(serde_json::to_string(&Bytes(1536)).unwrap(), "\"1536 B\"");
+        assert_eq!(serde_json::to_string(&Bytes(0)).unwrap(), "\"0 B\"");
+    }
+
+    #[test]
+    fn test_string_round_trip() {
+        for size in [Bytes(0), Bytes(12), Bytes(1536), Kilobytes(512).into(), MegaBytes(8).into(), Bytes(MB + KB)] {
+            let json = serde_json::to_string(&size).unwrap();
+            assert_eq!(serde_json::from_str::<Bytes>(&json).unwrap(), size, "round trip of {json}");
+        }
+        let config: Config = serde_json::from_str(r#"{ "cache_size": "512 KB" }"#).unwrap();
+        assert_eq!(config, Config { cache_size: Kilobytes(512).into() });
+    }
+
+    #[test]
+    fn test_integer_round_trip() {
+        let config: Config = serde_json::from_str(r#"{ "cache_size": 1048576 }"#).unwrap();
+        assert_eq!(config.cache_size, MegaBytes(1));
+        let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(json, r#"{"cache_size":"1 MB"}"#);
+        assert_eq!(serde_json::from_str::<Config>(&json).unwrap(), config);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(serde_json::from_str::<Bytes>("-5").is_err());
+        assert!(serde_json::from_str::<Bytes>("\"5 XB\"").is_err());
+        assert!(serde_json::from_str::<Bytes>("1.5").is_err());
+    }
+}
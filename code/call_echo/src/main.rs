@@ -1,16 +1,86 @@
-use std::io::Write;
-use std::process::{Command, Stdio};
+use std::io::{self, Write};
+use std::process::Stdio;
+use std::thread;
 
-fn main() {
-    let mut echo = Command::new("../target/debug/echo")
+use process_helpers::{find_sibling_binary, ProcessSpec};
+
+/// What came back from a run of `echo`.
+#[derive(Debug)]
+struct EchoResult {
+    stdout: String,
+    /// `None` if the process was ended by a signal.
+    exit_code: Option<i32>,
+}
+
+/// Sends `input` to the `echo` program and collects what it prints.
+fn run_echo(input: &str) -> io::Result<EchoResult> {
+    let echo = find_sibling_binary("echo").ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            "Can't find the echo binary; build it with `cargo build -p echo`",
+        )
+    })?;
+    run_with_input(&ProcessSpec::new(echo), input)
+}
+
+fn run_with_input(spec: &ProcessSpec, input: &str) -> io::Result<EchoResult> {
+    let mut child = spec
+        .command()
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
-        .spawn()
-        .unwrap();
+        .spawn()?;
+
+    // Write from another thread: if the child fills its stdout pipe before
+    // we've sent everything, it stops reading and we'd both wait forever.
+    let mut stdin = child.stdin.take().expect("No stdin on child");
+    let input = input.to_string();
+    let writer = thread::spawn(move || match stdin.write_all(input.as_bytes()) {
+        // The child closed stdin without reading everything. That's its
+        // choice; we still want its output and exit code.
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        other => other,
+    });
+
+    let output = child.wait_with_output()?;
+    writer.join().expect("Writer thread panicked")?;
+    Ok(EchoResult {
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        exit_code: output.status.code(),
+    })
+}
+
+fn main() {
+    let result = run_echo("Hello, world!\n").unwrap();
+    print!("echo said: {}", result.stdout);
+    match result.exit_code {
+        Some(code) => println!("echo exited with code {code}"),
+        None => println!("echo was terminated by a signal"),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let result = run_with_input(&ProcessSpec::new("cat"), "Hello, world!\n").unwrap();
+        assert_eq!(result.stdout, "Hello, world!\n");
+        assert_eq!(result.exit_code, Some(0));
+    }
 
-    let echo_stdin = echo.stdin.as_mut().unwrap();
+    #[test]
+    fn test_large_input() {
+        let input = "All work and no play\n".repeat(50_000);
+        let result = run_with_input(&ProcessSpec::new("cat"), &input).unwrap();
+        assert_eq!(result.stdout, input);
+    }
 
-    echo_stdin.write_all(b"Hello, world!\n").unwrap();
-    let output = echo.wait_with_output().unwrap();
-    println!("output = {:?}", output);
-}
\ No newline at end of file
+    #[test]
+    fn test_child_closes_stdin_early() {
+        let spec = ProcessSpec::new("sh").args(["-c", "exec 0<&-; echo done; exit 3"]);
+        let result = run_with_input(&spec, &"x".repeat(1_000_000)).unwrap();
+        assert_eq!(result.stdout, "done\n");
+        assert_eq!(result.exit_code, Some(3));
+    }
+}
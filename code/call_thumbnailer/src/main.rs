@@ -1,12 +1,36 @@
-use std::process::Command;
+use std::io::Read;
+use std::process::Stdio;
+use std::time::Duration;
+
+use process_helpers::{find_sibling_binary, wait_with_timeout, ProcessSpec};
 
 fn main() {
-    let result = Command::new("../target/release/thumbnailer")
+    let Some(thumbnailer) = find_sibling_binary("thumbnailer") else {
+        println!("Can't find the thumbnailer binary; build it with `cargo build -p thumbnailer`");
+        return;
+    };
+    let mut child = ProcessSpec::new(thumbnailer)
         .args(["../photo.jpg", "thumbnail.jpg"])
-        .output();
+        .command()
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Couldn't start the thumbnailer");
 
-    if let Ok(output) = result {
-        let returned_text = String::from_utf8(output.stdout).unwrap();
-        println!("Process returned: {returned_text}");
+    // The thumbnailer only prints a line, so it can't fill the pipe and
+    // stall while we wait.
+    match wait_with_timeout(&mut child, Duration::from_secs(5)) {
+        Ok(Some(_)) => {
+            let mut returned_text = String::new();
+            if let Some(mut stdout) = child.stdout.take() {
+                stdout.read_to_string(&mut returned_text).unwrap();
+            }
+            println!("Process returned: {returned_text}");
+        }
+        Ok(None) => {
+            println!("Thumbnailer took too long; killing it");
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+        Err(err) => println!("Couldn't wait for the thumbnailer: {err}"),
     }
 }
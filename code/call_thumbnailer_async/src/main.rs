@@ -1,15 +1,84 @@
+use std::ffi::OsStr;
+use std::io;
 use std::time::Duration;
+use process_helpers::find_sibling_binary;
+use thiserror::Error;
 use tokio::process::Command;
 use tokio::time::timeout;
 
+#[derive(Error, Debug)]
+enum CallError {
+    #[error("the process didn't finish in time")]
+    Timeout,
+    #[error("unable to start the process: {0}")]
+    Spawn(#[from] io::Error),
+    /// A process ended by a signal has no exit code and is reported as `-1`.
+    #[error("the process exited with code {0}")]
+    NonZeroExit(i32),
+}
+
+/// Runs the thumbnailer with `args`, returning what it printed.
+async fn call_thumbnailer(args: &[&str], limit: Duration) -> Result<String, CallError> {
+    let thumbnailer = find_sibling_binary("thumbnailer").ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            "Can't find the thumbnailer binary; build it with `cargo build -p thumbnailer`",
+        )
+    })?;
+    run_with_timeout(thumbnailer, args, limit).await
+}
+
+/// Runs `program`, giving up (and killing it) if it takes longer than `limit`.
+async fn run_with_timeout(
+    program: impl AsRef<OsStr>,
+    args: &[&str],
+    limit: Duration,
+) -> Result<String, CallError> {
+    let future = Command::new(program)
+        .args(args)
+        .kill_on_drop(true)
+        .output();
+
+    let output = timeout(limit, future).await.map_err(|_| CallError::Timeout)??;
+    if !output.status.success() {
+        return Err(CallError::NonZeroExit(output.status.code().unwrap_or(-1)));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
 #[tokio::main]
 async fn main() {
-    let future = Command::new("../target/release/thumbnailer")
-        .args(["../photo.jpg", "thumbnail.jpg"])
-        .output();
+    match call_thumbnailer(&["../photo.jpg", "thumbnail.jpg"], Duration::from_secs(1)).await {
+        Ok(returned_text) => println!("Process returned: {returned_text}"),
+        Err(err) => println!("Thumbnailer failed: {err}"),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_success() {
+        let output = run_with_timeout("echo", &["hello"], Duration::from_secs(5)).await.unwrap();
+        assert_eq!(output, "hello\n");
+    }
+
+    #[tokio::test]
+    async fn test_timeout() {
+        let result = run_with_timeout("sleep", &["5"], Duration::from_millis(1)).await;
+        assert!(matches!(result, Err(CallError::Timeout)));
+    }
+
+    #[tokio::test]
+    async fn test_spawn_error() {
+        let result = run_with_timeout("no-such-program", &[], Duration::from_secs(5)).await;
+        assert!(matches!(result, Err(CallError::Spawn(_))));
+    }
 
-    if let Ok(Ok(output)) = timeout(Duration::from_secs(1), future).await {
-        let returned_text = String::from_utf8(output.stdout).unwrap();
-        println!("Process returned: {returned_text}");
+    #[tokio::test]
+    async fn test_non_zero_exit() {
+        let result = run_with_timeout("sh", &["-c", "exit 4"], Duration::from_secs(5)).await;
+        assert!(matches!(result, Err(CallError::NonZeroExit(4))));
     }
 }
@@ -1,12 +1,199 @@
-use std::{process, thread, time::Duration};
+use std::io::{self, Read};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use std::{process, thread};
+
+use process_helpers::{long_running_command, wait_with_timeout, ProcessSpec};
+
+/// Spawns the process described by `make_spec`, retrying up to `attempts` times
+/// in total with `backoff` between tries. Useful when the program may not
+/// be on disk yet. Returns the last error if every attempt fails.
+fn spawn_with_retry(
+    make_spec: impl Fn() -> ProcessSpec,
+    attempts: usize,
+    backoff: Duration,
+) -> io::Result<process::Child> {
+    let mut last_error = io::Error::new(io::ErrorKind::InvalidInput, "No attempts to spawn");
+    for attempt in 1..=attempts {
+        match make_spec().spawn() {
+            Ok(child) => return Ok(child),
+            Err(err) => last_error = err,
+        }
+        if attempt < attempts {
+            thread::sleep(backoff);
+        }
+    }
+    Err(last_error)
+}
+
+/// What [`run_bounded`] captured.
+#[derive(Debug)]
+struct BoundedOutput {
+    output: process::Output,
+    /// True if the command was killed for running past the timeout, so the
+    /// output stops wherever it had got to.
+    truncated: bool,
+}
+
+/// How long [`run_bounded`] keeps collecting output once the child has
+/// exited or been killed. Anything the child left running (a grandchild of
+/// `sh -c`, say) can hold the pipes open indefinitely.
+const DRAIN_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Reads `pipe` on its own thread, sending each chunk down the returned
+/// channel until EOF.
+fn read_chunks(mut pipe: impl Read + Send + 'static) -> mpsc::Receiver<Vec<u8>> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buffer = [0; 4096];
+        loop {
+            match pipe.read(&mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => {
+                    if tx.send(buffer[..n].to_vec()).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+    rx
+}
+
+/// Collects chunks from `chunks` until the sender hangs up or `deadline`
+/// passes, whichever comes first.
+fn collect_until(chunks: &mpsc::Receiver<Vec<u8>>, deadline: Instant) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    while let Ok(chunk) = chunks.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+        buffer.extend_from_slice(&chunk);
+    }
+    buffer
+}
+
+/// Runs `spec`, collecting its stdout and stderr, and kills it if it's still
+/// running after `timeout`. Output written before the kill is kept.
+fn run_bounded(spec: &ProcessSpec, timeout: Duration) -> io::Result<BoundedOutput> {
+    let mut child = spec
+        .command()
+        .stdout(process::Stdio::piped())
+        .stderr(process::Stdio::piped())
+        .spawn()?;
+
+    // Read both pipes on their own threads, so a chatty child can't block
+    // on a full pipe while we're waiting for it to exit
+    let stdout = read_chunks(child.stdout.take().expect("No stdout on child"));
+    let stderr = read_chunks(child.stderr.take().expect("No stderr on child"));
+
+    let (status, truncated) = match wait_with_timeout(&mut child, timeout)? {
+        Some(status) => (status, false),
+        None => {
+            child.kill()?;
+            (child.wait()?, true)
+        }
+    };
+
+    // Don't wait for EOF: the pipes only close once everything holding
+    // them has exited
+    let deadline = Instant::now() + DRAIN_TIMEOUT;
+    let output = process::Output {
+        status,
+        stdout: collect_until(&stdout, deadline),
+        stderr: collect_until(&stderr, deadline),
+    };
+    Ok(BoundedOutput { output, truncated })
+}
 
 fn main() {
-    let mut child = process::Command::new("ping")
-        .arg("8.8.8.8")
-        .arg("-t")
-        .spawn()
-        .expect("Couldn't run 'ping'");
+    let mut child = spawn_with_retry(long_running_command, 3, Duration::from_millis(500))
+        .expect("Couldn't run the long-running command");
 
     thread::sleep(Duration::from_secs(5));
     child.kill().expect("!kill");
-}
\ No newline at end of file
+    child.wait().expect("!wait");
+
+    // This time, keep what it printed
+    let bounded = run_bounded(&long_running_command(), Duration::from_secs(3))
+        .expect("Couldn't run the long-running command");
+    print!("{}", String::from_utf8_lossy(&bounded.output.stdout));
+    if bounded.truncated {
+        println!("Killed after 3 seconds");
+    } else {
+        println!("Exited with {}", bounded.output.status);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_spawn_with_retry() {
+        // Fail twice, then find the program
+        let calls = std::cell::Cell::new(0);
+        let make_spec = || {
+            calls.set(calls.get() + 1);
+            if calls.get() <= 2 {
+                ProcessSpec::new("no-such-program")
+            } else {
+                ProcessSpec::new("true")
+            }
+        };
+        let mut child = spawn_with_retry(make_spec, 5, Duration::from_millis(10)).unwrap();
+        assert!(child.wait().unwrap().success());
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[test]
+    fn test_spawn_with_retry_gives_up() {
+        let calls = std::cell::Cell::new(0);
+        let make_spec = || {
+            calls.set(calls.get() + 1);
+            ProcessSpec::new("no-such-program")
+        };
+        let err = spawn_with_retry(make_spec, 3, Duration::from_millis(10)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert_eq!(calls.get(), 3);
+
+        let err = spawn_with_retry(make_spec, 0, Duration::from_millis(10)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_bounded_completes() {
+        let spec = ProcessSpec::new("sh").args(["-c", "echo out; echo err >&2"]);
+        let bounded = run_bounded(&spec, Duration::from_secs(5)).unwrap();
+        assert!(!bounded.truncated);
+        assert!(bounded.output.status.success());
+        assert_eq!(bounded.output.stdout, b"out\n");
+        assert_eq!(bounded.output.stderr, b"err\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_bounded_truncates() {
+        // `exec` so the kill reaches `sleep`, rather than leaving it holding
+        // the pipes open
+        let spec = ProcessSpec::new("sh").args(["-c", "echo started; exec sleep 10"]);
+        let started = Instant::now();
+        let bounded = run_bounded(&spec, Duration::from_millis(300)).unwrap();
+        assert!(bounded.truncated);
+        assert!(!bounded.output.status.success());
+        assert_eq!(bounded.output.stdout, b"started\n");
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_bounded_grandchild_holding_pipes() {
+        // Without `exec`, killing `sh` leaves `sleep` holding the pipes open
+        let spec = ProcessSpec::new("sh").args(["-c", "echo started; sleep 10; true"]);
+        let started = Instant::now();
+        let bounded = run_bounded(&spec, Duration::from_millis(300)).unwrap();
+        assert!(bounded.truncated);
+        assert_eq!(bounded.output.stdout, b"started\n");
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -1,38 +1,375 @@
-use std::io::Read;
+use std::io::{self, BufRead, BufReader, Read};
 use std::process::*;
+use std::sync::mpsc;
 use std::thread;
-use std::time::Duration;
-
-fn wait_on_output(mut out: ChildStdout) {
-    //while out.read_exact(&mut [0; 1024]).is_ok() {}
-    let mut buf = [0; 1024];
-    while let Ok(n) = out.read(&mut buf) {
-        if n == 0 {
-            break;
+use std::time::{Duration, Instant};
+
+use process_helpers::{long_running_command, wait_with_timeout};
+
+/// How to stop a child that's still running when its time is up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KillMode {
+    /// Ask it to exit with SIGTERM, then kill it if it's still running after
+    /// the grace period. Windows has no SIGTERM, so there this is the same
+    /// as `Forceful`.
+    Graceful(Duration),
+    /// Kill it straight away (SIGKILL on Unix).
+    Forceful,
+}
+
+/// How the child process ended.
+#[derive(Debug)]
+enum Exit {
+    /// The process exited on its own within the time limit.
+    Completed(ExitStatus),
+    /// The process was still running when the time limit expired, and was
+    /// stopped as the [`KillMode`] said.
+    Killed,
+}
+
+/// The result of running a process under [`wait_or_kill`].
+#[derive(Debug)]
+struct ProcessOutcome {
+    exit: Exit,
+    /// Everything the process wrote to stdout.
+    stdout: Vec<u8>,
+    /// Everything the process wrote to stderr.
+    stderr: Vec<u8>,
+}
+
+/// How long to keep collecting output once the child has stopped. Anything
+/// it left running (a grandchild of `sh -c`, say) can hold the pipes open
+/// indefinitely, so we don't wait for them to close.
+const DRAIN_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Which of the child's pipes a line came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pipe {
+    Stdout,
+    Stderr,
+}
+
+/// Reads `out` on its own thread until EOF, sending each complete line
+/// (with its line ending) down `lines`.
+///
+/// Reading whole lines means a multi-byte character is never split between
+/// two lines. Invalid UTF-8 is passed on as-is.
+fn read_lines(out: impl Read + Send + 'static, pipe: Pipe, lines: mpsc::Sender<(Pipe, Vec<u8>)>) {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(out);
+        loop {
+            let mut line = Vec::new();
+            match reader.read_until(b'\n', &mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {
+                    if lines.send((pipe, line)).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Stops `child` as `mode` says, returning once it has exited or been
+/// killed.
+fn stop(child: &mut Child, mode: KillMode) -> io::Result<()> {
+    match mode {
+        #[cfg(unix)]
+        KillMode::Graceful(grace) => {
+            use nix::sys::signal::{kill, Signal};
+            use nix::unistd::Pid;
+
+            kill(Pid::from_raw(child.id() as i32), Signal::SIGTERM)?;
+            if wait_with_timeout(child, grace)?.is_some() {
+                return Ok(());
+            }
+            println!("Child ignored SIGTERM; killing it");
+            child.kill()
         }
-        println!("Read {n} bytes");
-        println!("{:?}", String::from_utf8(buf[..n].to_vec()));
+        _ => child.kill(),
     }
 }
 
-fn wait_or_kill(cmd: &mut Command, max: Duration) {
-    let mut child = cmd.stdout(Stdio::piped())
-                       .spawn()
-                       .expect("Cannot spawn child");
+/// Runs `cmd`, echoing its output, and stops it as `mode` says if it's
+/// still running after `max`.
+fn wait_or_kill(cmd: &mut Command, max: Duration, mode: KillMode) -> io::Result<ProcessOutcome> {
+    wait_or_kill_with(cmd, max, mode, |line| println!("stdout: {line}"))
+}
+
+/// Like [`wait_or_kill`], but hands each line of stdout (without its line
+/// ending, and decoded lossily) to `on_line` as soon as it arrives.
+///
+/// Returns within about `max` plus the time it takes to stop the child,
+/// even if something else is still holding the child's pipes open.
+fn wait_or_kill_with(
+    cmd: &mut Command,
+    max: Duration,
+    mode: KillMode,
+    mut on_line: impl FnMut(&str),
+) -> io::Result<ProcessOutcome> {
+    let mut child = cmd.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+    let deadline = Instant::now() + max;
 
+    // Read both pipes on their own threads, so a child that fills one pipe
+    // while we're blocked reading the other can't deadlock us.
+    let (lines_tx, lines_rx) = mpsc::channel();
     let out = child.stdout.take().expect("No stdout on child");
+    let err = child.stderr.take().expect("No stderr on child");
+    read_lines(out, Pipe::Stdout, lines_tx.clone());
+    read_lines(err, Pipe::Stderr, lines_tx);
 
-    let h = thread::spawn(move || {
-        thread::sleep(max);
-        println!("Killing child process");
-        child.kill().expect("Cannot kill child process");
-        println!("{:?}", child.wait());
-    });
+    let mut stdout = Vec::new();
+    let mut stderr = Vec::new();
+    let mut handle_line = |(pipe, line): (Pipe, Vec<u8>)| {
+        let text = String::from_utf8_lossy(&line);
+        let text = text.trim_end_matches(['\n', '\r']);
+        match pipe {
+            Pipe::Stdout => {
+                on_line(text);
+                stdout.extend_from_slice(&line);
+            }
+            Pipe::Stderr => {
+                println!("stderr: {text}");
+                stderr.extend_from_slice(&line);
+            }
+        }
+    };
+
+    // Echo output until both pipes close or time runs out
+    while let Ok(line) = lines_rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+        handle_line(line);
+    }
+
+    // Closing the pipes doesn't mean the process has exited, so keep
+    // checking until the deadline.
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    let exit = match wait_with_timeout(&mut child, remaining)? {
+        Some(status) => Exit::Completed(status),
+        None => {
+            println!("Stopping child process");
+            stop(&mut child, mode)?;
+            println!("{:?}", child.wait()?);
+            Exit::Killed
+        }
+    };
 
-    wait_on_output(out);
-    h.join().expect("join fail");
+    // Pick up whatever the child wrote before it stopped, without waiting
+    // on pipes that something it left behind may never close
+    let drain_until = Instant::now() + DRAIN_TIMEOUT;
+    while let Ok(line) =
+        lines_rx.recv_timeout(drain_until.saturating_duration_since(Instant::now()))
+    {
+        handle_line(line);
+    }
+
+    Ok(ProcessOutcome {
+        exit,
+        stdout,
+        stderr,
+    })
 }
 
 fn main() {
-    wait_or_kill(Command::new("ping").args(["8.8.8.8", "-t"]), Duration::new(2, 0));
-}
\ No newline at end of file
+    // Pass --force to skip the SIGTERM and kill the child outright
+    let mode = if std::env::args().any(|arg| arg == "--force") {
+        KillMode::Forceful
+    } else {
+        KillMode::Graceful(Duration::from_secs(1))
+    };
+    let outcome = wait_or_kill(
+        &mut long_running_command().command(),
+        Duration::new(2, 0),
+        mode,
+    )
+    .expect("Cannot run child process");
+    match outcome.exit {
+        Exit::Completed(status) => println!("Child exited with {status}"),
+        Exit::Killed => println!("Child was killed"),
+    }
+    println!(
+        "Captured {} bytes of output and {} bytes of errors",
+        outcome.stdout.len(),
+        outcome.stderr.len()
+    );
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_completes() {
+        let outcome = wait_or_kill(
+            Command::new("echo").arg("hello"),
+            Duration::from_millis(500),
+            KillMode::Forceful,
+        )
+        .unwrap();
+        assert!(matches!(outcome.exit, Exit::Completed(status) if status.success()));
+        assert_eq!(outcome.stdout, b"hello\n");
+    }
+
+    #[test]
+    fn test_fast_command_is_not_killed() {
+        let started = Instant::now();
+        let outcome = wait_or_kill(
+            &mut Command::new("true"),
+            Duration::from_secs(10),
+            KillMode::Forceful,
+        )
+        .unwrap();
+        assert!(matches!(outcome.exit, Exit::Completed(status) if status.success()));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_closed_output_still_waits_for_exit() {
+        // Closes stdout and stderr, then keeps running past the limit
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "exec >&- 2>&-; sleep 10"]);
+        let outcome =
+            wait_or_kill(&mut cmd, Duration::from_millis(200), KillMode::Forceful).unwrap();
+        assert!(matches!(outcome.exit, Exit::Killed));
+    }
+
+    #[test]
+    fn test_killed() {
+        let outcome = wait_or_kill(
+            Command::new("sleep").arg("10"),
+            Duration::from_millis(200),
+            KillMode::Forceful,
+        )
+        .unwrap();
+        assert!(matches!(outcome.exit, Exit::Killed));
+        assert!(outcome.stdout.is_empty());
+    }
+
+    #[test]
+    fn test_grandchild_holding_pipes_does_not_block() {
+        // Without `exec`, killing `sh` leaves `sleep` holding the pipes open
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "echo started; sleep 10; true"]);
+        let started = Instant::now();
+        let outcome =
+            wait_or_kill(&mut cmd, Duration::from_millis(500), KillMode::Forceful).unwrap();
+        assert!(matches!(outcome.exit, Exit::Killed));
+        assert_eq!(outcome.stdout, b"started\n");
+        assert!(
+            started.elapsed() < Duration::from_secs(5),
+            "waited for the grandchild to close the pipes"
+        );
+    }
+
+    #[test]
+    fn test_captures_stderr() {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "echo out; echo oops >&2"]);
+        let outcome =
+            wait_or_kill(&mut cmd, Duration::from_millis(500), KillMode::Forceful).unwrap();
+        assert_eq!(outcome.stdout, b"out\n");
+        assert_eq!(outcome.stderr, b"oops\n");
+    }
+
+    #[test]
+    fn test_large_stderr_does_not_deadlock() {
+        // Far more than a pipe buffer holds on either stream
+        let mut cmd = Command::new("sh");
+        cmd.args([
+            "-c",
+            "head -c 200000 /dev/zero >&2; head -c 200000 /dev/zero",
+        ]);
+        let outcome = wait_or_kill(&mut cmd, Duration::from_secs(5), KillMode::Forceful).unwrap();
+        assert_eq!(outcome.stderr.len(), 200_000);
+        assert_eq!(outcome.stdout.len(), 200_000);
+    }
+
+    #[test]
+    fn test_long_running_command_is_killed() {
+        let outcome = wait_or_kill(
+            &mut long_running_command().command(),
+            Duration::from_millis(1500),
+            KillMode::Forceful,
+        )
+        .unwrap();
+        assert!(matches!(outcome.exit, Exit::Killed));
+        assert!(!outcome.stdout.is_empty());
+    }
+
+    #[test]
+    fn test_lines_arrive_intact() {
+        // One line is long enough to span several reads of the pipe
+        let long = "é".repeat(5000);
+        let mut cmd = Command::new("sh");
+        cmd.args([
+            "-c",
+            &format!("printf 'héllo wörld\\n日本語\\r\\n{long}\\nno newline'"),
+        ]);
+
+        let mut lines = Vec::new();
+        let outcome = wait_or_kill_with(
+            &mut cmd,
+            Duration::from_secs(5),
+            KillMode::Forceful,
+            |line| lines.push(line.to_string()),
+        )
+        .unwrap();
+        assert_eq!(
+            lines,
+            ["héllo wörld", "日本語", long.as_str(), "no newline"]
+        );
+        assert_eq!(
+            outcome.stdout,
+            format!("héllo wörld\n日本語\r\n{long}\nno newline").as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_spawn_error() {
+        assert!(wait_or_kill(
+            &mut Command::new("no-such-program"),
+            Duration::from_millis(100),
+            KillMode::Forceful
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_graceful_stop() {
+        // Cleans up and exits as soon as it's asked to
+        let mut cmd = Command::new("sh");
+        cmd.args([
+            "-c",
+            "trap 'echo cleaning up; exit 0' TERM; while true; do sleep 0.1; done",
+        ]);
+        let started = Instant::now();
+        let outcome = wait_or_kill(
+            &mut cmd,
+            Duration::from_millis(300),
+            KillMode::Graceful(Duration::from_secs(10)),
+        )
+        .unwrap();
+        assert!(matches!(outcome.exit, Exit::Killed));
+        assert_eq!(outcome.stdout, b"cleaning up\n");
+        assert!(
+            started.elapsed() < Duration::from_secs(5),
+            "waited out the grace period"
+        );
+    }
+
+    #[test]
+    fn test_graceful_stop_falls_back_to_kill() {
+        // Ignores SIGTERM, so has to be killed once the grace period is over
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "trap '' TERM; while true; do sleep 0.1; done"]);
+        let started = Instant::now();
+        let outcome = wait_or_kill(
+            &mut cmd,
+            Duration::from_millis(200),
+            KillMode::Graceful(Duration::from_millis(500)),
+        )
+        .unwrap();
+        assert!(matches!(outcome.exit, Exit::Killed));
+        assert!(started.elapsed() >= Duration::from_millis(700));
+    }
+}
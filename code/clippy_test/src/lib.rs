@@ -0,0 +1,37 @@
+#![warn(clippy::pedantic)]
+//! The `needless_range_loop` lint, and how to fix it.
+
+/// Sums `numbers` by indexing into the slice. This is the pattern clippy
+/// warns about: the index is only used to look up the element, and every
+/// lookup is bounds-checked.
+#[must_use]
+#[allow(clippy::needless_range_loop)]
+pub fn sum_indexed(numbers: &[i32]) -> i32 {
+    let mut total = 0;
+    for i in 0..numbers.len() {
+        total += numbers[i];
+    }
+    total
+}
+
+/// Sums `numbers` by iterating over them, which is what clippy suggests.
+#[must_use]
+pub fn sum_idiomatic(numbers: &[i32]) -> i32 {
+    numbers.iter().sum()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sums_agree() {
+        let inputs: [&[i32]; 5] = [&[], &[42], &[1, 2, 3], &[-5, 5, -10], &[7; 1000]];
+        for numbers in inputs {
+            assert_eq!(sum_indexed(numbers), sum_idiomatic(numbers), "{numbers:?}");
+        }
+        let numbers = (0..100).collect::<Vec<i32>>();
+        assert_eq!(sum_indexed(&numbers), 4950);
+        assert_eq!(sum_idiomatic(&numbers), 4950);
+    }
+}
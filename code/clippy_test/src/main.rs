@@ -1,12 +1,11 @@
 #![warn(clippy::pedantic)]
+use clippy_test::{sum_idiomatic, sum_indexed};
+
 fn main() {
     let numbers = (0..100).collect::<Vec<i32>>();
-    for i in 0 .. numbers.len() {
-        println!("{}", numbers[i]);
-    }
+    // The wrong way: see `sum_indexed`, which has to allow the lint
+    println!("{}", sum_indexed(&numbers));
 
     // The right way
-    //for i in &numbers {
-    //    println!("{i}");
-    //}
+    println!("{}", sum_idiomatic(&numbers));
 }
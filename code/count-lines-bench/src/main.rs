@@ -0,0 +1,157 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use count_lines::count_readtostring;
+use count_lines::timing::time_it;
+use count_lines_buffer::count_buffered;
+use count_lines_mmap::count_mmap;
+
+type Strategy = fn(&Path) -> io::Result<usize>;
+
+const STRATEGIES: [(&str, Strategy); 3] = [
+    ("read_to_string", count_readtostring),
+    ("buffered", count_buffered),
+    ("mmap", count_mmap),
+];
+
+/// Mean and standard deviation of a set of timings, in milliseconds.
+struct Summary {
+    mean: f64,
+    std_dev: f64,
+}
+
+fn summarize(timings: &[Duration]) -> Summary {
+    let samples: Vec<f64> = timings.iter().map(|t| t.as_secs_f64() * 1000.0).collect();
+    let n = samples.len() as f64;
+    let mean = samples.iter().sum::<f64>() / n;
+    let variance = samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / n;
+    Summary {
+        mean,
+        std_dev: variance.sqrt(),
+    }
+}
+
+/// Runs `strategy` against `path` `runs` times, returning the line count
+/// and the time taken by each run.
+fn bench(strategy: Strategy, path: &Path, runs: usize) -> io::Result<(usize, Vec<Duration>)> {
+    let mut count = 0;
+    let mut timings = Vec::with_capacity(runs);
+    for _ in 0..runs {
+        let (result, elapsed) = time_it(|| strategy(path));
+        count = result?;
+        timings.push(elapsed);
+    }
+    Ok((count, timings))
+}
+
+/// Reads `<file> [runs]` from the command line, with `runs` defaulting to
+/// 10.
+fn parse_args(args: &[String]) -> io::Result<(PathBuf, usize)> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidInput, message);
+    if args.len() < 2 || args.len() > 3 {
+        return Err(invalid("Usage: count-lines-bench <file> [runs]"));
+    }
+    let runs = match args.get(2) {
+        Some(runs) => runs
+            .parse()
+            .map_err(|_| invalid("runs must be a positive number"))?,
+        None => 10,
+    };
+    if runs == 0 {
+        return Err(invalid("runs must be at least 1"));
+    }
+    Ok((PathBuf::from(&args[1]), runs))
+}
+
+fn main() -> io::Result<()> {
+    let args = std::env::args().collect::<Vec<_>>();
+    let (path, runs) = parse_args(&args)?;
+
+    for (name, strategy) in STRATEGIES {
+        let (count, timings) = bench(strategy, &path, runs)?;
+        let summary = summarize(&timings);
+        println!(
+            "{name:>15}: {count} lines, mean {:.2} ms, std dev {:.2} ms over {runs} runs",
+            summary.mean, summary.std_dev
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_strategies_agree() {
+        let path = Path::new("../warandpeace.txt");
+        let counts: Vec<usize> = STRATEGIES
+            .iter()
+            .map(|(_, strategy)| strategy(path).unwrap())
+            .collect();
+        assert!(counts.iter().all(|c| *c == counts[0]));
+    }
+
+    #[test]
+    fn test_bench_records_each_run() {
+        let (count, timings) = bench(count_buffered, Path::new("Cargo.toml"), 3).unwrap();
+        assert!(count > 0);
+        assert_eq!(timings.len(), 3);
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args = |list: &[&str]| list.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        let (path, runs) = parse_args(&args(&["bench", "file.txt"])).unwrap();
+        assert_eq!((path, runs), (PathBuf::from("file.txt"), 10));
+        let (_, runs) = parse_args(&args(&["bench", "file.txt", "3"])).unwrap();
+        assert_eq!(runs, 3);
+
+        for bad in [
+            &["bench"][..],
+            &["bench", "file.txt", "3", "extra"],
+            &["bench", "file.txt", "many"],
+            &["bench", "file.txt", "0"],
+        ] {
+            let err = parse_args(&args(bad)).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput, "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn test_summarize() {
+        let timings = [2, 4, 4, 4, 5, 5, 7, 9].map(Duration::from_millis);
+        let summary = summarize(&timings);
+        assert!((summary.mean - 5.0).abs() < 1e-9);
+        assert!((summary.std_dev - 2.0).abs() < 1e-9);
+    }
+
+    /// Inputs every strategy must agree on, with the expected line count.
+    const FIXTURES: [(&str, &[u8], usize); 8] = [
+        ("empty", b"", 0),
+        ("single newline", b"\n", 1),
+        ("trailing newline", b"a\n", 1),
+        ("no trailing newline", b"a\nb", 2),
+        ("blank lines", b"a\n\nb\n", 3),
+        ("crlf", b"a\r\nb\r\n", 2),
+        ("crlf no trailing newline", b"a\r\nb", 2),
+        ("mixed endings", b"a\nb\r\nc", 3),
+    ];
+
+    #[test]
+    fn test_strategies_agree_on_fixtures() {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, contents, expected) in FIXTURES {
+            let path = dir.path().join(name);
+            std::fs::write(&path, contents).unwrap();
+            for (strategy_name, strategy) in STRATEGIES {
+                assert_eq!(
+                    strategy(&path).unwrap(),
+                    expected,
+                    "{strategy_name} miscounted the '{name}' fixture"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use std::{io::{self, BufRead, BufReader}, fs::File, path::Path};
+
+/// Counts the lines in a file, reading it through a `BufReader` rather than
+/// loading the whole file into memory.
+///
+/// Lines are counted the same way as `count_lines::count_readtostring`:
+/// an empty file is 0 lines, `"a\n"` is 1 and `"a\nb"` is 2.
+pub fn count_buffered(path: &Path) -> io::Result<usize> {
+    let file = File::open(path)?;
+    count_lines::count_reader(BufReader::new(file))
+}
+
+/// How many bytes to read between calls to the progress callback.
+const PROGRESS_INTERVAL: u64 = 1024 * 1024;
+
+/// Counts the lines in a file like [`count_buffered`], calling `on_progress`
+/// with the number of bytes read so far roughly every megabyte.
+///
+/// The callback is always called once more at the end with the total size,
+/// so even a small file reports its progress.
+pub fn count_lines_with_progress(path: &Path, mut on_progress: impl FnMut(u64)) -> io::Result<usize> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut line = Vec::new();
+    let mut lines = 0;
+    let mut bytes_read = 0;
+    let mut next_report = PROGRESS_INTERVAL;
+    loop {
+        line.clear();
+        let n = reader.read_until(b'\n', &mut line)?;
+        if n == 0 {
+            break;
+        }
+        lines += 1;
+        bytes_read += n as u64;
+        if bytes_read >= next_report {
+            on_progress(bytes_read);
+            next_report = bytes_read + PROGRESS_INTERVAL;
+        }
+    }
+    on_progress(bytes_read);
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_count_lines_with_progress() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let contents = "a line of text\n".repeat(300_000);
+        std::fs::write(file.path(), &contents).unwrap();
+
+        let mut reports = Vec::new();
+        let count = count_lines_with_progress(file.path(), |bytes| reports.push(bytes)).unwrap();
+        assert_eq!(count, 300_000);
+        assert_eq!(count, count_buffered(file.path()).unwrap());
+
+        // 4.5MB read gives a report for each megabyte, plus the final total
+        assert_eq!(reports.len(), 5);
+        assert!(reports.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(*reports.last().unwrap(), contents.len() as u64);
+    }
+
+    #[test]
+    fn test_count_lines_with_progress_small_file() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), "one\ntwo\nthree").unwrap();
+        let mut reports = Vec::new();
+        assert_eq!(count_lines_with_progress(file.path(), |bytes| reports.push(bytes)).unwrap(), 3);
+        assert_eq!(reports, [13]);
+    }
+}
@@ -0,0 +1,164 @@
+use std::{io::{self, BufReader}, fs::File, path::Path};
+use count_lines::count_reader;
+use memmap2::MmapOptions;
+use rayon::prelude::*;
+
+/// Chunks smaller than this aren't worth handing to another thread.
+const MIN_CHUNK: usize = 1024 * 1024;
+
+/// Counts the lines in a file by memory-mapping it and reading the mapped
+/// bytes through a `BufReader`. Mapping errors are returned as-is, which keeps
+/// benchmarks honest; use [`count_lines_mmap`] if you want a fallback.
+///
+/// Lines are counted the same way as `count_lines::count_readtostring`:
+/// an empty file is 0 lines, `"a\n"` is 1 and `"a\nb"` is 2.
+pub fn count_mmap(path: &Path) -> io::Result<usize> {
+    let file = File::open(path)?;
+    // Some platforms refuse to map a zero-length file, and it has no lines anyway
+    if file.metadata()?.len() == 0 {
+        return Ok(0);
+    }
+    // Safety: the mapping is only read, and lives no longer than this function.
+    let mmap = unsafe { MmapOptions::new().map(&file)? };
+    count_reader(BufReader::new(&mmap[..]))
+}
+
+/// Counts the lines in a file using a memory map where possible.
+///
+/// Empty files return 0 without being mapped. If the file can't be mapped
+/// (some filesystems and platforms don't support it), it is read through a
+/// regular `BufReader` instead.
+pub fn count_lines_mmap(path: &Path) -> io::Result<usize> {
+    let file = File::open(path)?;
+    if file.metadata()?.len() == 0 {
+        return Ok(0);
+    }
+    // Safety: the mapping is only read, and lives no longer than this function.
+    match unsafe { MmapOptions::new().map(&file) } {
+        Ok(mmap) => count_reader(BufReader::new(&mmap[..])),
+        Err(_) => count_reader(BufReader::new(file)),
+    }
+}
+
+/// Splits `bytes` into about `n` chunks of similar size. Every chunk but the
+/// last ends just after a `\n`, so no line is split between two chunks.
+fn newline_chunks(bytes: &[u8], n: usize) -> Vec<&[u8]> {
+    let target = (bytes.len() / n.max(1)).max(1);
+    let mut chunks = Vec::with_capacity(n);
+    let mut rest = bytes;
+    while rest.len() > target {
+        // Move the split forward to the next newline, keeping it in this chunk
+        match rest[target..].iter().position(|b| *b == b'\n') {
+            Some(offset) => {
+                let (chunk, tail) = rest.split_at(target + offset + 1);
+                chunks.push(chunk);
+                rest = tail;
+            }
+            None => break,
+        }
+    }
+    if !rest.is_empty() {
+        chunks.push(rest);
+    }
+    chunks
+}
+
+/// Counts the lines in a file by memory-mapping it and counting newlines in
+/// parallel with Rayon.
+///
+/// The mapped bytes are split into one chunk per thread, each ending on a
+/// newline, and the newlines in each chunk are summed. A final line without a
+/// trailing newline is counted once, so the result matches [`count_mmap`].
+pub fn count_lines_mmap_parallel(path: &Path) -> io::Result<usize> {
+    let file = File::open(path)?;
+    if file.metadata()?.len() == 0 {
+        return Ok(0);
+    }
+    // Safety: the mapping is only read, and lives no longer than this function.
+    let mmap = unsafe { MmapOptions::new().map(&file)? };
+    let bytes = &mmap[..];
+    let threads = rayon::current_num_threads().min(bytes.len() / MIN_CHUNK).max(1);
+    let newlines: usize = newline_chunks(bytes, threads)
+        .par_iter()
+        .map(|chunk| chunk.iter().filter(|b| **b == b'\n').count())
+        .sum();
+    let unterminated = usize::from(bytes.last() != Some(&b'\n'));
+    Ok(newlines + unterminated)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_empty_file() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        assert_eq!(count_lines_mmap(file.path()).unwrap(), 0);
+        assert_eq!(count_mmap(file.path()).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_count_lines_mmap() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), "one\ntwo\nthree").unwrap();
+        assert_eq!(count_lines_mmap(file.path()).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let err = count_lines_mmap(Path::new("does-not-exist.txt")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_newline_chunks() {
+        let bytes = b"aaaa\nbb\ncccccc\nd\neeee";
+        let chunks = newline_chunks(bytes, 3);
+        assert_eq!(chunks.concat(), bytes);
+        for chunk in &chunks[..chunks.len() - 1] {
+            assert_eq!(chunk.last(), Some(&b'\n'));
+        }
+        // A chunk with no newline after the target can't be split
+        assert_eq!(newline_chunks(b"abcdef", 4), vec![b"abcdef"]);
+        assert!(newline_chunks(b"", 4).is_empty());
+    }
+
+    #[test]
+    fn test_count_lines_mmap_parallel() {
+        for contents in ["", "\n", "a", "a\n", "a\nb", "a\r\nb\r\n", "a\n\nb\n"] {
+            let file = tempfile::NamedTempFile::new().unwrap();
+            std::fs::write(file.path(), contents).unwrap();
+            assert_eq!(
+                count_lines_mmap_parallel(file.path()).unwrap(),
+                count_mmap(file.path()).unwrap(),
+                "miscounted {contents:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_count_lines_mmap_parallel_large_file() {
+        // Lines of varying length, so chunk boundaries land mid-line
+        let mut contents = String::new();
+        let mut i = 0;
+        while contents.len() < 8 * 1024 * 1024 {
+            contents.push_str(&"x".repeat(i % 200));
+            contents.push('\n');
+            i += 1;
+        }
+        contents.push_str("no trailing newline");
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), &contents).unwrap();
+
+        let expected = count_mmap(file.path()).unwrap();
+        assert_eq!(expected, i + 1);
+        assert_eq!(count_lines_mmap_parallel(file.path()).unwrap(), expected);
+
+        // Force several chunks even on a single-core machine
+        let bytes = contents.as_bytes();
+        let chunks = newline_chunks(bytes, 8);
+        assert!(chunks.len() >= 8);
+        let newlines: usize = chunks.iter().map(|c| c.iter().filter(|b| **b == b'\n').count()).sum();
+        assert_eq!(newlines + 1, expected);
+    }
+}
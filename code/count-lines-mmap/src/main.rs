@@ -1,11 +1,17 @@
-use std::{io::{BufRead, BufReader}, fs::File};
-use memmap2::MmapOptions;
+use count_lines::timing::time_it;
+use std::path::{Path, PathBuf};
 
+/// Pass a path to count, or `-` to count stdin. Stdin can't be mapped, so
+/// it's read through a `BufReader`.
 fn main() {
-    let now = std::time::Instant::now();
-    let file = File::open("../warandpeace.txt").unwrap();
-    let mmap = unsafe { MmapOptions::new().map(&file).unwrap() };
-    let buffered_reader = BufReader::new(&mmap[..]);
-    println!("Line count: {}", buffered_reader.lines().count());
-    println!("Completed in {} ms", now.elapsed().as_millis());
+    let path = std::env::args().nth(1).map_or_else(|| PathBuf::from("../warandpeace.txt"), PathBuf::from);
+    let (count, elapsed) = time_it(|| {
+        if path == Path::new(count_lines::STDIN_PATH) {
+            count_lines::count_reader(std::io::stdin().lock())
+        } else {
+            count_lines_mmap::count_lines_mmap(&path)
+        }
+    });
+    println!("Line count: {}", count.unwrap());
+    println!("Completed in {} ms", elapsed.as_millis());
 }
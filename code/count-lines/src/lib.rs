@@ -0,0 +1,338 @@
+//! Line counting helpers.
+//!
+//! All of the line counting strategies in the `count-lines` family follow the
+//! same convention as [`str::lines`]: a line ends at `\n` (with an optional
+//! preceding `\r`), and a final line without a trailing newline still counts.
+//! So an empty file has 0 lines, `"a\n"` has 1, and `"a\nb"` has 2.
+
+pub mod timing;
+
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::read_to_string;
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// Counts the lines in a single file by reading it into a `String`.
+/// See the [crate docs](crate) for how lines are counted.
+pub fn count_readtostring(path: &Path) -> io::Result<usize> {
+    Ok(read_to_string(path)?.lines().count())
+}
+
+/// The path that means "read from stdin" on the command line.
+pub const STDIN_PATH: &str = "-";
+
+/// Counts the lines from any buffered reader, so files and stdin share the
+/// same code. Lines are split on `\n` without being decoded, so this works
+/// for any encoding that ends lines that way.
+pub fn count_reader(reader: impl BufRead) -> io::Result<usize> {
+    let mut lines = 0;
+    for line in reader.split(b'\n') {
+        line?;
+        lines += 1;
+    }
+    Ok(lines)
+}
+
+/// Opens `path` for buffered reading, or locks stdin if it is
+/// [`STDIN_PATH`].
+pub fn open_input(path: &Path) -> io::Result<Box<dyn BufRead>> {
+    if path == Path::new(STDIN_PATH) {
+        Ok(Box::new(io::stdin().lock()))
+    } else {
+        Ok(Box::new(BufReader::new(std::fs::File::open(path)?)))
+    }
+}
+
+/// Counts the lines in many files at once, using Rayon to spread the work
+/// across threads.
+///
+/// Each path gets its own result, in the same order as `paths`, so a file
+/// that can't be read doesn't stop the others from being counted.
+pub fn count_lines_parallel(paths: &[PathBuf]) -> Vec<(PathBuf, io::Result<usize>)> {
+    paths
+        .par_iter()
+        .map(|path| (path.clone(), count_readtostring(path)))
+        .collect()
+}
+
+/// A `wc`-style summary of a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Counts {
+    pub lines: usize,
+    /// Whitespace-separated words
+    pub words: usize,
+    /// Length of the file in bytes
+    pub bytes: usize,
+    /// Unicode scalar values, which differs from `bytes` for non-ASCII text
+    pub chars: usize,
+}
+
+/// Counts the lines, words, bytes and characters in a UTF-8 text file.
+/// A leading byte order mark is skipped, as with [`count_file_with`].
+pub fn count_file(path: &Path) -> io::Result<Counts> {
+    count_file_with(path, Encoding::Utf8).map_err(io::Error::from)
+}
+
+/// The UTF-8 byte order mark some editors put at the start of a file.
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// How to turn a file's bytes into text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    /// UTF-8, with an optional byte order mark. Invalid bytes are an error.
+    #[default]
+    Utf8,
+    /// ISO-8859-1, where every byte is one character. This never fails, so
+    /// it's also a way to count files of unknown encoding.
+    Latin1,
+}
+
+/// Why a file couldn't be counted.
+#[derive(Debug)]
+pub enum CountError {
+    /// The file couldn't be read.
+    Io(io::Error),
+    /// The file isn't valid UTF-8. `valid_up_to` is the offset of the first
+    /// bad byte.
+    InvalidUtf8 { valid_up_to: usize },
+}
+
+impl fmt::Display for CountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CountError::Io(err) => write!(f, "I/O error: {err}"),
+            CountError::InvalidUtf8 { valid_up_to } => {
+                write!(f, "invalid UTF-8 at byte {valid_up_to}; try counting it as Latin-1")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CountError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CountError::Io(err) => Some(err),
+            CountError::InvalidUtf8 { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for CountError {
+    fn from(err: io::Error) -> Self {
+        CountError::Io(err)
+    }
+}
+
+impl From<CountError> for io::Error {
+    fn from(err: CountError) -> Self {
+        match err {
+            CountError::Io(err) => err,
+            other => io::Error::new(io::ErrorKind::InvalidData, other),
+        }
+    }
+}
+
+/// Decodes `bytes` as `encoding`. A UTF-8 byte order mark is removed rather
+/// than becoming part of the first line.
+pub fn decode(bytes: Vec<u8>, encoding: Encoding) -> Result<String, CountError> {
+    match encoding {
+        Encoding::Utf8 => {
+            let mut bytes = bytes;
+            if bytes.starts_with(UTF8_BOM) {
+                bytes.drain(..UTF8_BOM.len());
+            }
+            String::from_utf8(bytes).map_err(|err| CountError::InvalidUtf8 {
+                valid_up_to: err.utf8_error().valid_up_to(),
+            })
+        }
+        // Latin-1 bytes are the first 256 Unicode code points
+        Encoding::Latin1 => Ok(bytes.into_iter().map(char::from).collect()),
+    }
+}
+
+/// Counts the lines, words, bytes and characters in a file, decoding it as
+/// `encoding`. `bytes` is the size of the file as read, including any byte
+/// order mark.
+pub fn count_file_with(path: &Path, encoding: Encoding) -> Result<Counts, CountError> {
+    let bytes = std::fs::read(path)?;
+    let len = bytes.len();
+    let text = decode(bytes, encoding)?;
+    Ok(Counts {
+        lines: text.lines().count(),
+        words: text.split_whitespace().count(),
+        bytes: len,
+        chars: text.chars().count(),
+    })
+}
+
+/// Counts how often each word appears in a UTF-8 text file.
+///
+/// Words are split on whitespace, lowercased, and have any leading or
+/// trailing punctuation removed, so `"The"`, `"the,"` and `"(the)"` are all
+/// counted as `"the"`. Punctuation inside a word, as in `"don't"`, is kept.
+pub fn word_frequencies(path: &Path) -> io::Result<HashMap<String, usize>> {
+    let text = decode(std::fs::read(path)?, Encoding::Utf8)?;
+    let mut freqs = HashMap::new();
+    for word in text.split_whitespace() {
+        let word = word.trim_matches(|c: char| !c.is_alphanumeric());
+        if !word.is_empty() {
+            *freqs.entry(word.to_lowercase()).or_insert(0) += 1;
+        }
+    }
+    Ok(freqs)
+}
+
+/// The `n` most common words in `freqs`, most common first. Words with the
+/// same count are sorted alphabetically.
+pub fn top_n(freqs: &HashMap<String, usize>, n: usize) -> Vec<(String, usize)> {
+    let mut sorted: Vec<(String, usize)> = freqs.iter().map(|(w, c)| (w.clone(), *c)).collect();
+    sorted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    sorted.truncate(n);
+    sorted
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_count_lines_parallel() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut paths = Vec::new();
+        for i in 1..=5 {
+            let path = dir.path().join(format!("{i}.txt"));
+            std::fs::write(&path, "line\n".repeat(i)).unwrap();
+            paths.push(path);
+        }
+        let missing = dir.path().join("missing.txt");
+        paths.insert(2, missing.clone());
+
+        let results = count_lines_parallel(&paths);
+        assert_eq!(results.len(), paths.len());
+        for (result, path) in results.iter().zip(paths.iter()) {
+            assert_eq!(&result.0, path);
+        }
+
+        let counts: Vec<Option<usize>> = results.iter().map(|(_, r)| r.as_ref().ok().copied()).collect();
+        assert_eq!(counts, vec![Some(1), Some(2), None, Some(3), Some(4), Some(5)]);
+        assert_eq!(results[2].1.as_ref().unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_count_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ascii.txt");
+        std::fs::write(&path, "Hello world\nThis is  a test\n").unwrap();
+        let counts = count_file(&path).unwrap();
+        assert_eq!(counts, Counts { lines: 2, words: 6, bytes: 28, chars: 28 });
+    }
+
+    #[test]
+    fn test_count_file_multibyte() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("utf8.txt");
+        // "Привет" is 6 chars but 12 bytes, "日本" is 2 chars but 6 bytes
+        std::fs::write(&path, "Привет мир\n日本\n").unwrap();
+        let counts = count_file(&path).unwrap();
+        assert_eq!(counts.lines, 2);
+        assert_eq!(counts.words, 3);
+        assert_eq!(counts.chars, 14);
+        assert_eq!(counts.bytes, 27);
+        assert_ne!(counts.bytes, counts.chars);
+    }
+
+    const DOCUMENT: &str = "The cat sat on the mat.\n\
+        The dog sat on the log!\n\
+        A cat and a dog: don't they (the cat, the dog) get along?\n";
+
+    #[test]
+    fn test_word_frequencies() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("doc.txt");
+        std::fs::write(&path, DOCUMENT).unwrap();
+        let freqs = word_frequencies(&path).unwrap();
+        assert_eq!(freqs["the"], 6);
+        assert_eq!(freqs["cat"], 3);
+        assert_eq!(freqs["don't"], 1);
+        assert!(!freqs.contains_key("The"));
+        assert!(!freqs.contains_key("mat."));
+    }
+
+    #[test]
+    fn test_top_n() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("doc.txt");
+        std::fs::write(&path, DOCUMENT).unwrap();
+        let freqs = word_frequencies(&path).unwrap();
+        let top = top_n(&freqs, 5);
+        let expected = [("the", 6), ("cat", 3), ("dog", 3), ("a", 2), ("on", 2)];
+        assert_eq!(top, expected.map(|(w, c)| (w.to_string(), c)));
+    }
+
+    #[test]
+    fn test_top_n_more_than_available() {
+        let freqs = HashMap::from([("b".to_string(), 1), ("a".to_string(), 1)]);
+        assert_eq!(top_n(&freqs, 10), vec![("a".to_string(), 1), ("b".to_string(), 1)]);
+        assert!(top_n(&freqs, 0).is_empty());
+    }
+
+    #[test]
+    fn test_bom_is_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bom.txt");
+        std::fs::write(&path, b"\xEF\xBB\xBFHello world\nsecond line\n").unwrap();
+        let counts = count_file(&path).unwrap();
+        assert_eq!(counts, Counts { lines: 2, words: 4, bytes: 27, chars: 24 });
+        let freqs = word_frequencies(&path).unwrap();
+        assert!(freqs.contains_key("hello"));
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("latin1.txt");
+        // "café\nniño\n" in Latin-1
+        std::fs::write(&path, b"caf\xE9\nni\xF1o\n").unwrap();
+
+        let err = count_file_with(&path, Encoding::Utf8).unwrap_err();
+        assert!(matches!(err, CountError::InvalidUtf8 { valid_up_to: 3 }));
+        assert_eq!(count_file(&path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        let counts = count_file_with(&path, Encoding::Latin1).unwrap();
+        assert_eq!(counts, Counts { lines: 2, words: 2, bytes: 10, chars: 10 });
+        assert_eq!(decode(b"caf\xE9".to_vec(), Encoding::Latin1).unwrap(), "café");
+    }
+
+    #[test]
+    fn test_missing_file_is_io_error() {
+        let err = count_file_with(Path::new("does-not-exist.txt"), Encoding::Latin1).unwrap_err();
+        assert!(matches!(err, CountError::Io(ref e) if e.kind() == io::ErrorKind::NotFound));
+        assert!(err.to_string().starts_with("I/O error"));
+    }
+
+    #[test]
+    fn test_count_reader() {
+        // A byte slice stands in for stdin
+        for (input, expected) in [
+            (&b""[..], 0),
+            (b"\n", 1),
+            (b"a\n", 1),
+            (b"a\nb", 2),
+            (b"a\r\nb\r\n", 2),
+            (b"caf\xE9\n\nni\xF1o", 3),
+        ] {
+            assert_eq!(count_reader(input).unwrap(), expected, "miscounted {input:?}");
+        }
+    }
+
+    #[test]
+    fn test_open_input_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lines.txt");
+        std::fs::write(&path, "one\ntwo\n").unwrap();
+        assert_eq!(count_reader(open_input(&path).unwrap()).unwrap(), 2);
+        assert!(open_input(&dir.path().join("missing.txt")).is_err());
+    }
+}
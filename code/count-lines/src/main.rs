@@ -1,8 +1,33 @@
-use std::fs::read_to_string;
+use count_lines::timing::time_it;
+use count_lines::STDIN_PATH;
+use std::io;
+use std::path::{Path, PathBuf};
 
 fn main() {
-    let now = std::time::Instant::now();
-    let war_and_peace = read_to_string("../warandpeace.txt").unwrap();
-    println!("Line count: {}", war_and_peace.lines().count());
-    println!("Completed in {} ms", now.elapsed().as_millis());
+    let paths: Vec<PathBuf> = std::env::args().skip(1).map(PathBuf::from).collect();
+    let ((), elapsed) = time_it(|| {
+        if paths.is_empty() {
+            let counts = count_lines::count_file(Path::new("../warandpeace.txt")).unwrap();
+            println!("Line count: {}", counts.lines);
+            println!("Word count: {}", counts.words);
+            println!("Byte count: {}", counts.bytes);
+            println!("Char count: {}", counts.chars);
+        } else {
+            let (stdin, files): (Vec<PathBuf>, Vec<PathBuf>) =
+                paths.into_iter().partition(|path| path == Path::new(STDIN_PATH));
+            if !stdin.is_empty() {
+                match count_lines::count_reader(io::stdin().lock()) {
+                    Ok(count) => println!("{STDIN_PATH}: {count}"),
+                    Err(err) => println!("{STDIN_PATH}: {err}"),
+                }
+            }
+            for (path, result) in count_lines::count_lines_parallel(&files) {
+                match result {
+                    Ok(count) => println!("{}: {count}", path.display()),
+                    Err(err) => println!("{}: {err}", path.display()),
+                }
+            }
+        }
+    });
+    println!("Completed in {} ms", elapsed.as_millis());
 }
@@ -0,0 +1,25 @@
+//! Timing shared by the `count-lines` family of binaries.
+
+use std::time::{Duration, Instant};
+
+/// Runs `f`, returning its result and how long it took.
+pub fn time_it<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let now = Instant::now();
+    let result = f();
+    (result, now.elapsed())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_time_it() {
+        let (value, elapsed) = time_it(|| 6 * 7);
+        assert_eq!(value, 42);
+        assert!(elapsed >= Duration::ZERO);
+
+        let (_, elapsed) = time_it(|| std::thread::sleep(Duration::from_millis(20)));
+        assert!(elapsed >= Duration::from_millis(20));
+    }
+}
@@ -8,9 +8,12 @@
 //! 
 //! ```
 //! use documented::example_function;
-//! example_function();
+//! example_function(2);
 //! ```
 
+use std::io::{self, Write};
+use std::ops::Mul;
+
 /// This is an example function. It prints a message including the value
 /// of `n`, and returns `n * 2`.
 /// 
@@ -28,8 +31,53 @@
 /// assert_eq!(documented::example_function(2), 4);
 /// ```
 pub fn example_function(n: i32) -> i32 {
-    println!("This is an example function. n = {n}");
-    n * 2
+    example_function_to(n, &mut std::io::stdout()).expect("Unable to write to stdout")
+}
+
+/// Like [`example_function`], but writes its message to `out` instead of
+/// stdout.
+///
+/// # Arguments
+///
+/// * `n` - The number to multiply by 2
+/// * `out` - Where to write the message
+///
+/// # Returns
+///
+/// The result of `n * 2`, or the error from writing to `out`
+///
+/// # Examples
+///
+/// ```
+/// let mut out = Vec::new();
+/// assert_eq!(documented::example_function_to(2, &mut out).unwrap(), 4);
+/// assert_eq!(out, b"This is an example function. n = 2\n");
+/// ```
+pub fn example_function_to(n: i32, out: &mut dyn Write) -> io::Result<i32> {
+    writeln!(out, "This is an example function. n = {n}")?;
+    Ok(scale(n, 2))
+}
+
+/// Multiplies `n` by `factor`, for any numeric type. Unlike
+/// [`example_function`], it has no side effects.
+///
+/// # Arguments
+///
+/// * `n` - The number to scale
+/// * `factor` - What to multiply it by
+///
+/// # Returns
+///
+/// The result of `n * factor`
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(documented::scale(3, 2), 6);
+/// assert_eq!(documented::scale(1.5, 3.0), 4.5);
+/// ```
+pub fn scale<T: Copy + Mul<Output = T>>(n: T, factor: T) -> T {
+    n * factor
 }
 
 /// Example of an unsafe function
@@ -38,6 +86,8 @@ pub fn example_function(n: i32) -> i32 {
 /// 
 /// This function uses `get_unchecked` for fast access to a vector. This is ok, because the 
 /// bounds of the vector are known ahead of time.
+// The example is about indexing a `Vec`, even though an array would do
+#[allow(clippy::useless_vec)]
 pub fn example_unsafe() -> i32 {
     let n = vec![1, 2, 3, 4, 5];
     unsafe {
@@ -69,3 +119,40 @@ pub mod frobnicator {
         println!("Frobnicating!");
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_example_function_to() {
+        let mut out = Vec::new();
+        assert_eq!(example_function_to(21, &mut out).unwrap(), 42);
+        assert_eq!(String::from_utf8(out).unwrap(), "This is an example function. n = 21\n");
+    }
+
+    #[test]
+    fn test_scale() {
+        assert_eq!(scale(7u8, 3), 21);
+        assert_eq!(scale(-4i64, 5), -20);
+        assert_eq!(scale(-3i8, 4), -12);
+        assert_eq!(scale(0.5f32, 4.0), 2.0);
+        assert_eq!(example_function(5), scale(5, 2));
+    }
+
+    #[test]
+    fn test_example_unsafe() {
+        assert_eq!(example_unsafe(), 4);
+    }
+
+    #[test]
+    fn test_panic_example() {
+        assert_eq!(panic_example(Some(3)), 6);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_panic_example_none() {
+        panic_example(None);
+    }
+}
@@ -0,0 +1,77 @@
+//! Helpers shared by the examples that serve files over HTTP.
+
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use std::path::{Component, Path, PathBuf};
+
+/// The file served when the request doesn't name one.
+pub const DEFAULT_FILE: &str = "Cargo.toml";
+
+/// Checks a client-supplied file name, rejecting absolute paths and any
+/// `..` components so requests can't wander outside the working directory.
+pub fn requested_path(file: &str) -> Result<PathBuf, (StatusCode, String)> {
+    let path = Path::new(file);
+    let safe = path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if !safe || file.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, format!("Invalid file path: {file}")));
+    }
+    Ok(path.to_path_buf())
+}
+
+/// Builds `Content-Type` (guessed from the extension) and
+/// `Content-Disposition` (using the file's base name) headers for `path`.
+pub fn file_headers(path: &Path) -> Result<HeaderMap, (StatusCode, String)> {
+    let mime = mime_guess::from_path(path).first_or_octet_stream();
+    let content_type = if mime.type_() == mime_guess::mime::TEXT {
+        format!("{mime}; charset=utf-8")
+    } else {
+        mime.to_string()
+    };
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("download");
+    let disposition = HeaderValue::from_str(&format!("attachment; filename=\"{file_name}\""))
+        .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid file name: {file_name}")))?;
+
+    let content_type = HeaderValue::from_str(&content_type).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Invalid content type: {content_type}"),
+        )
+    })?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(header::CONTENT_TYPE, content_type);
+    headers.insert(header::CONTENT_DISPOSITION, disposition);
+    Ok(headers)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_requested_path() {
+        assert_eq!(requested_path("Cargo.toml").unwrap(), PathBuf::from("Cargo.toml"));
+        assert_eq!(requested_path("./src/main.rs").unwrap(), PathBuf::from("./src/main.rs"));
+        for bad in ["", "/etc/passwd", "../secret", "src/../../secret"] {
+            let (status, _) = requested_path(bad).unwrap_err();
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_file_headers() {
+        let headers = file_headers(Path::new("src/notes.txt")).unwrap();
+        assert_eq!(headers[header::CONTENT_TYPE], "text/plain; charset=utf-8");
+        assert_eq!(headers[header::CONTENT_DISPOSITION], "attachment; filename=\"notes.txt\"");
+
+        let headers = file_headers(Path::new("photo.jpg")).unwrap();
+        assert_eq!(headers[header::CONTENT_TYPE], "image/jpeg");
+
+        let (status, _) = file_headers(Path::new("bad\nname.txt")).unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
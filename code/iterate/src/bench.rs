@@ -0,0 +1,73 @@
+use std::time::{Duration, Instant};
+
+/// Timings from running a closure several times with [`bench`].
+#[derive(Debug, Clone)]
+pub struct BenchResult {
+    pub label: String,
+    pub runs: usize,
+    pub min: Duration,
+    pub max: Duration,
+    pub mean: Duration,
+}
+
+impl std::fmt::Display for BenchResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: mean {:.2?}, min {:.2?}, max {:.2?} over {} runs",
+            self.label, self.mean, self.min, self.max, self.runs
+        )
+    }
+}
+
+/// Runs `f` `runs` times, timing each run.
+///
+/// The result of each run is passed through [`std::hint::black_box`] so the
+/// compiler can't skip work whose result is never used.
+///
+/// # Panics
+///
+/// If `runs` is zero, since there would be nothing to summarize.
+pub fn bench<T>(label: &str, runs: usize, f: impl Fn() -> T) -> BenchResult {
+    assert!(runs > 0, "bench needs at least one run");
+    let mut timings = Vec::with_capacity(runs);
+    for _ in 0..runs {
+        let now = Instant::now();
+        std::hint::black_box(f());
+        timings.push(now.elapsed());
+    }
+    BenchResult {
+        label: label.to_string(),
+        runs,
+        min: *timings.iter().min().unwrap(),
+        max: *timings.iter().max().unwrap(),
+        mean: timings.iter().sum::<Duration>() / runs as u32,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_bench() {
+        let calls = Cell::new(0);
+        let result = bench("sleepy", 5, || {
+            calls.set(calls.get() + 1);
+            std::thread::sleep(Duration::from_millis(calls.get()));
+        });
+        assert_eq!(calls.get(), 5);
+        assert_eq!(result.runs, 5);
+        assert_eq!(result.label, "sleepy");
+        assert!(result.min <= result.mean && result.mean <= result.max);
+        assert!(result.min >= Duration::from_millis(1));
+        assert!(result.max >= Duration::from_millis(5));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bench_needs_a_run() {
+        bench("nothing", 0, || ());
+    }
+}
@@ -0,0 +1,178 @@
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+use rayon::ThreadPoolBuilder;
+
+mod bench;
+mod rows;
+pub use bench::{bench, BenchResult};
+pub use rows::{build_index, find_greeting, find_row, get_rows, longest_greeting, DuplicateLanguage, Greetings, Row};
+
+/// Trial division primality test. Deliberately simple (and slow) so the
+/// example can compare sequential, iterator and parallel versions.
+pub fn is_prime(n: u32) -> bool {
+    n >= 2 && (2 ..= n/2).all(|i| n % i != 0)
+}
+
+/// Counts the primes below `max` with a plain `for` loop.
+pub fn count_primes_loop(max: u32) -> usize {
+    let mut count = 0;
+    for n in 2..max {
+        if is_prime(n) {
+            count += 1;
+        }
+    }
+    count
+}
+
+/// Counts the primes below `max` with an iterator chain.
+pub fn count_primes_iter(max: u32) -> usize {
+    (2..max).filter(|n| is_prime(*n)).count()
+}
+
+/// Counts the primes below `max` with a Rayon parallel iterator, running on
+/// a pool of its own with `threads` threads rather than Rayon's global pool.
+/// That keeps timings comparable between machines, and lets you see how the
+/// work scales. A `threads` of 0 lets Rayon pick, as the global pool does.
+///
+/// # Panics
+///
+/// If the thread pool can't be created.
+pub fn count_primes_parallel(max: u32, threads: usize) -> usize {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .expect("Unable to build the thread pool");
+    pool.install(|| (2..max).into_par_iter().filter(|n| is_prime(*n)).count())
+}
+
+/// Returns every prime from 2 up to and including `max`, in order.
+pub fn primes_up_to(max: u32) -> Vec<u32> {
+    (2..=max)
+        .filter(|n| is_prime(*n))
+        .collect()
+}
+
+/// Parallel version of [`primes_up_to`], using Rayon.
+///
+/// Rayon doesn't promise to keep the original order when filtering, so the
+/// result is sorted before it is returned.
+pub fn primes_up_to_parallel(max: u32) -> Vec<u32> {
+    let mut primes: Vec<u32> = (2..=max)
+        .into_par_iter()
+        .filter(|n| is_prime(*n))
+        .collect();
+    primes.sort_unstable();
+    primes
+}
+
+/// Sieve of Eratosthenes. Returns a table where `table[n]` is `true` if `n`
+/// is prime, for every `n` from 0 up to and including `max`.
+///
+/// This is far faster than calling [`is_prime`] on every number, because
+/// each composite is crossed out by its prime factors instead of being
+/// tested by trial division.
+pub fn sieve(max: u32) -> Vec<bool> {
+    let max = max as usize;
+    let mut is_prime = vec![true; max + 1];
+    is_prime[0] = false;
+    if max >= 1 {
+        is_prime[1] = false;
+    }
+    let mut n = 2;
+    while n * n <= max {
+        if is_prime[n] {
+            for multiple in (n * n..=max).step_by(n) {
+                is_prime[multiple] = false;
+            }
+        }
+        n += 1;
+    }
+    is_prime
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_primes_up_to() {
+        assert_eq!(primes_up_to(20), vec![2, 3, 5, 7, 11, 13, 17, 19]);
+        assert_eq!(primes_up_to(13).last(), Some(&13));
+        assert!(primes_up_to(1).is_empty());
+    }
+
+    #[test]
+    fn test_parallel_primes_agree() {
+        let sequential = primes_up_to(10_000);
+        let parallel = primes_up_to_parallel(10_000);
+        assert_eq!(sequential.len(), 1229);
+        assert_eq!(sequential, parallel);
+    }
+
+    #[test]
+    fn test_count_strategies_agree() {
+        for max in [0, 1, 2, 3, 10, 100, 1_000, 7_919, 20_000] {
+            let expected = count_primes_loop(max);
+            assert_eq!(count_primes_iter(max), expected, "iterator count below {max}");
+            assert_eq!(count_primes_parallel(max, 4), expected, "parallel count below {max}");
+        }
+        assert_eq!(count_primes_loop(100), 25);
+        assert_eq!(count_primes_loop(10_000), 1229);
+    }
+
+    #[test]
+    fn test_parallel_thread_counts_agree() {
+        let expected = count_primes_loop(20_000);
+        for threads in [1, 2, 4] {
+            assert_eq!(count_primes_parallel(20_000, threads), expected, "{threads} threads");
+        }
+    }
+
+    #[test]
+    #[ignore = "timing-sensitive; run alone with `cargo test -- --ignored`"]
+    fn test_parallel_is_not_slower() {
+        // A smoke test, not a benchmark: on a single core there's nothing to
+        // gain, so only catch the parallel version being far slower. It's
+        // ignored by default because the other tests compete for the CPU;
+        // `test_count_strategies_agree` covers correctness.
+        const MAX: u32 = 50_000;
+        let now = std::time::Instant::now();
+        let sequential = count_primes_loop(MAX);
+        let sequential_time = now.elapsed();
+
+        let now = std::time::Instant::now();
+        let parallel = count_primes_parallel(MAX, 0);
+        let parallel_time = now.elapsed();
+
+        assert_eq!(sequential, parallel);
+        assert!(
+            parallel_time <= sequential_time * 2 + std::time::Duration::from_millis(100),
+            "parallel took {parallel_time:?}, sequential took {sequential_time:?}"
+        );
+    }
+
+    #[test]
+    fn test_is_prime() {
+        assert!(!is_prime(0));
+        assert!(!is_prime(1));
+        assert!(is_prime(2));
+        assert!(is_prime(3));
+        assert!(!is_prime(4));
+    }
+
+    #[test]
+    fn test_sieve() {
+        let table = sieve(20);
+        let primes: Vec<u32> = (0..=20).filter(|n| table[*n as usize]).collect();
+        assert_eq!(primes, vec![2, 3, 5, 7, 11, 13, 17, 19]);
+        assert_eq!(sieve(0), vec![false]);
+        assert_eq!(sieve(1), vec![false, false]);
+    }
+
+    #[test]
+    fn test_sieve_matches_trial_division() {
+        const MAX: u32 = 100_000;
+        let sieve_count = sieve(MAX).iter().filter(|p| **p).count();
+        let trial_count = primes_up_to_parallel(MAX).len();
+        assert_eq!(sieve_count, trial_count);
+    }
+}
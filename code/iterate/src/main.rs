@@ -1,104 +1,4 @@
-struct Row {
-    language: String,
-    message: String,
-}
-
-fn get_rows() -> Vec<Row> {
-    vec![
-        Row {
-            language: "English".to_string(),
-            message: "Hello".to_string(),
-        },
-        Row {
-            language: "French".to_string(),
-            message: "Bonjour".to_string(),
-        },
-        Row {
-            language: "Spanish".to_string(),
-            message: "Hola".to_string(),
-        },
-        Row {
-            language: "Russian".to_string(),
-            message: "Zdravstvuyte".to_string(),
-        },
-        Row {
-            language: "Chinese".to_string(),
-            message: "Nǐn hǎo".to_string(),
-        },
-        Row {
-            language: "Italian".to_string(),
-            message: "Salve".to_string(),
-        },
-        Row {
-            language: "Japanese".to_string(),
-            message: "Konnichiwa".to_string(),
-        },
-        Row {
-            language: "German".to_string(),
-            message: "Guten Tag".to_string(),
-        },
-        Row {
-            language: "Portuguese".to_string(),
-            message: "Olá".to_string(),
-        },
-        Row {
-            language: "Korean".to_string(),
-            message: "Anyoung haseyo".to_string(),
-        },
-        Row {
-            language: "Arabic".to_string(),
-            message: "Asalaam alaikum".to_string(),
-        },
-        Row {
-            language: "Danish".to_string(),
-            message: "Goddag".to_string(),
-        },
-        Row {
-            language: "Swahili".to_string(),
-            message: "Shikamoo".to_string(),
-        },
-        Row {
-            language: "Dutch".to_string(),
-            message: "Goedendag".to_string(),
-        },
-        Row {
-            language: "Greek".to_string(),
-            message: "Yassas".to_string(),
-        },
-        Row {
-            language: "Polish".to_string(),
-            message: "Dzień dobry".to_string(),
-        },
-        Row {
-            language: "Indonesian".to_string(),
-            message: "Selamat siang".to_string(),
-        },
-        Row {
-            language: "Hindi".to_string(),
-            message: "Namaste, Namaskar".to_string(),
-        },
-        Row {
-            language: "Norwegian".to_string(),
-            message: "God dag".to_string(),
-        },
-        Row {
-            language: "Turkish".to_string(),
-            message: "Merhaba".to_string(),
-        },
-        Row {
-            language: "Hebrew".to_string(),
-            message: "Shalom".to_string(),
-        },
-        Row {
-            language: "Swedish".to_string(),
-            message: "God dag".to_string(),
-        },
-    ]
-}
-
-fn is_prime(n: u32) -> bool {
-    (2 ..= n/2).all(|i| n % i != 0 )
- }
+use iterate::{bench, find_greeting, find_row, get_rows, longest_greeting, Greetings};
 
 fn main() {
     let now = std::time::Instant::now();
@@ -111,36 +11,60 @@ fn main() {
     }
     println!("Elapsed: {} nanos", now.elapsed().as_nanos());
 
+    // `filter` keeps scanning every row after the match
     let now = std::time::Instant::now();
     rows.iter()
         .filter(|r| r.language == "French")
         .for_each(|r| println!("{}", r.message));
     println!("Elapsed: {} nanos", now.elapsed().as_nanos());
 
-    // Working with primes
+    // Like the loop with `break`, `find` stops at the first match
+    let now = std::time::Instant::now();
+    if let Some(row) = find_row(&rows, |r| r.language == "French") {
+        println!("{}", row.message);
+    }
+    println!("Elapsed: {} nanos", now.elapsed().as_nanos());
+
     let now = std::time::Instant::now();
+    if let Some(message) = find_greeting(&rows, "french") {
+        println!("{message}");
+    }
+    println!("Elapsed: {} nanos", now.elapsed().as_nanos());
+
+    if let Some(row) = longest_greeting(&rows) {
+        println!("Longest greeting: {row}");
+    }
+
+    // Index once, then look up as often as we like
+    let greetings = Greetings::try_from(get_rows()).expect("Duplicate language in rows");
+    let now = std::time::Instant::now();
+    for language in ["French", "Spanish", "Swahili"] {
+        println!("{language}: {}", greetings.get(language).unwrap_or("?"));
+    }
+    println!("Elapsed: {} nanos", now.elapsed().as_nanos());
+
+    // Working with primes: loop, iterator and parallel iterator
     const MAX:u32 = 200000;
-    let mut count = 0;
-    for n in 2 .. MAX {
-        if is_prime(n) {
-            count+=1;
-        }
+    const RUNS: usize = 3;
+    println!("{}", bench("loop", RUNS, || iterate::count_primes_loop(MAX)));
+    println!("{}", bench("iterator", RUNS, || iterate::count_primes_iter(MAX)));
+    // See how the parallel version scales with more threads
+    let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+    for threads in [1, 2, 4, cores] {
+        let label = format!("parallel ({threads} threads)");
+        println!("{}", bench(&label, RUNS, || iterate::count_primes_parallel(MAX, threads)));
     }
-    println!("Found {count} primes in {:.2} seconds", now.elapsed().as_secs_f32());
 
-    // Iterator for primes
+    // Sieve of Eratosthenes
     let now = std::time::Instant::now();
-    let count = (2..MAX)
-        .filter(|n| is_prime(*n))
+    let count = iterate::sieve(MAX - 1)
+        .iter()
+        .filter(|is_prime| **is_prime)
         .count();
-    println!("Found {count} primes in {:.2} seconds", now.elapsed().as_secs_f32());
+    println!("Found {count} primes in {:.2} seconds (sieve)", now.elapsed().as_secs_f32());
 
-    // Parallel Iterator for primes
-    use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+    // Collecting the primes themselves
     let now = std::time::Instant::now();
-    let count = (2..MAX)
-        .into_par_iter()
-        .filter(|n| is_prime(*n))
-        .count();
-    println!("Found {count} primes in {:.2} seconds", now.elapsed().as_secs_f32());
+    let primes = iterate::primes_up_to_parallel(MAX);
+    println!("Collected {} primes (largest {:?}) in {:.2} seconds", primes.len(), primes.last(), now.elapsed().as_secs_f32());
 }
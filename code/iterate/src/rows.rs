@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+
+/// A greeting in a given language, as it might come back from a database.
+#[derive(Debug, Clone)]
+pub struct Row {
+    pub language: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for Row {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.language, self.message)
+    }
+}
+
+/// Sample greetings in 22 languages.
+pub fn get_rows() -> Vec<Row> {
+    vec![
+        Row {
+            language: "English".to_string(),
+            message: "Hello".to_string(),
+        },
+        Row {
+            language: "French".to_string(),
+            message: "Bonjour".to_string(),
+        },
+        Row {
+            language: "Spanish".to_string(),
+            message: "Hola".to_string(),
+        },
+        Row {
+            language: "Russian".to_string(),
+            message: "Zdravstvuyte".to_string(),
+        },
+        Row {
+            language: "Chinese".to_string(),
+            message: "Nǐn hǎo".to_string(),
+        },
+        Row {
+            language: "Italian".to_string(),
+            message: "Salve".to_string(),
+        },
+        Row {
+            language: "Japanese".to_string(),
+            message: "Konnichiwa".to_string(),
+        },
+        Row {
+            language: "German".to_string(),
+            message: "Guten Tag".to_string(),
+        },
+        Row {
+            language: "Portuguese".to_string(),
+            message: "Olá".to_string(),
+        },
+        Row {
+            language: "Korean".to_string(),
+            message: "Anyoung haseyo".to_string(),
+        },
+        Row {
+            language: "Arabic".to_string(),
+            message: "Asalaam alaikum".to_string(),
+        },
+        Row {
+            language: "Danish".to_string(),
+            message: "Goddag".to_string(),
+        },
+        Row {
+            language: "Swahili".to_string(),
+            message: "Shikamoo".to_string(),
+        },
+        Row {
+            language: "Dutch".to_string(),
+            message: "Goedendag".to_string(),
+        },
+        Row {
+            language: "Greek".to_string(),
+            message: "Yassas".to_string(),
+        },
+        Row {
+            language: "Polish".to_string(),
+            message: "Dzień dobry".to_string(),
+        },
+        Row {
+            language: "Indonesian".to_string(),
+            message: "Selamat siang".to_string(),
+        },
+        Row {
+            language: "Hindi".to_string(),
+            message: "Namaste, Namaskar".to_string(),
+        },
+        Row {
+            language: "Norwegian".to_string(),
+            message: "God dag".to_string(),
+        },
+        Row {
+            language: "Turkish".to_string(),
+            message: "Merhaba".to_string(),
+        },
+        Row {
+            language: "Hebrew".to_string(),
+            message: "Shalom".to_string(),
+        },
+        Row {
+            language: "Swedish".to_string(),
+            message: "God dag".to_string(),
+        },
+    ]
+}
+
+/// Looks up the greeting for `language`, ignoring ASCII case, by scanning
+/// the rows in order.
+pub fn find_greeting<'a>(rows: &'a [Row], language: &str) -> Option<&'a str> {
+    rows.iter()
+        .find(|row| row.language.eq_ignore_ascii_case(language))
+        .map(|row| row.message.as_str())
+}
+
+/// Returns the first row matching `predicate`. Unlike
+/// `filter(..).for_each(..)`, this stops scanning as soon as it finds a
+/// match, so `predicate` isn't called for any of the rows after it.
+pub fn find_row(rows: &[Row], mut predicate: impl FnMut(&Row) -> bool) -> Option<&Row> {
+    rows.iter().find(|row| predicate(row))
+}
+
+/// Returns the row with the longest message, counted in characters rather
+/// than bytes. If several are equally long, the one whose language comes
+/// first alphabetically wins.
+pub fn longest_greeting(rows: &[Row]) -> Option<&Row> {
+    rows.iter().max_by(|a, b| {
+        a.message
+            .chars()
+            .count()
+            .cmp(&b.message.chars().count())
+            .then_with(|| b.language.cmp(&a.language))
+    })
+}
+
+/// Returned when the same language appears more than once while building an
+/// index, holding the offending language name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateLanguage(pub String);
+
+impl std::fmt::Display for DuplicateLanguage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "duplicate language: {}", self.0)
+    }
+}
+
+impl std::error::Error for DuplicateLanguage {}
+
+/// Builds a map from lowercase language name to greeting. Languages that
+/// differ only by case count as duplicates.
+pub fn build_index(rows: Vec<Row>) -> Result<HashMap<String, String>, DuplicateLanguage> {
+    let mut index = HashMap::with_capacity(rows.len());
+    for row in rows {
+        let key = row.language.to_lowercase();
+        if index.contains_key(&key) {
+            return Err(DuplicateLanguage(row.language));
+        }
+        index.insert(key, row.message);
+    }
+    Ok(index)
+}
+
+/// An index of greetings for repeated lookups, without rescanning the rows
+/// each time.
+pub struct Greetings {
+    index: HashMap<String, String>,
+}
+
+impl Greetings {
+    /// Gets the greeting for `language`, ignoring case.
+    pub fn get(&self, language: &str) -> Option<&str> {
+        self.index
+            .get(&language.to_lowercase())
+            .map(|message| message.as_str())
+    }
+}
+
+impl TryFrom<Vec<Row>> for Greetings {
+    type Error = DuplicateLanguage;
+
+    fn try_from(rows: Vec<Row>) -> Result<Self, Self::Error> {
+        Ok(Greetings {
+            index: build_index(rows)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_find_greeting_exact() {
+        let rows = get_rows();
+        assert_eq!(find_greeting(&rows, "French"), Some("Bonjour"));
+    }
+
+    #[test]
+    fn test_find_greeting_ignores_case() {
+        let rows = get_rows();
+        assert_eq!(find_greeting(&rows, "french"), Some("Bonjour"));
+        assert_eq!(find_greeting(&rows, "GERMAN"), Some("Guten Tag"));
+    }
+
+    #[test]
+    fn test_find_greeting_missing() {
+        let rows = get_rows();
+        assert_eq!(find_greeting(&rows, "Klingon"), None);
+    }
+
+    #[test]
+    fn test_find_row_stops_at_first_match() {
+        let rows = get_rows();
+        let calls = std::cell::Cell::new(0);
+        let found = find_row(&rows, |row| {
+            calls.set(calls.get() + 1);
+            row.language == "French"
+        });
+        assert_eq!(found.map(|row| row.message.as_str()), Some("Bonjour"));
+        // English, then French
+        assert_eq!(calls.get(), 2);
+
+        // `filter` looks at every row, even after the match
+        calls.set(0);
+        rows.iter()
+            .filter(|row| {
+                calls.set(calls.get() + 1);
+                row.language == "French"
+            })
+            .for_each(drop);
+        assert_eq!(calls.get(), rows.len());
+    }
+
+    #[test]
+    fn test_find_row_missing() {
+        let rows = get_rows();
+        let calls = std::cell::Cell::new(0);
+        let found = find_row(&rows, |row| {
+            calls.set(calls.get() + 1);
+            row.language == "Klingon"
+        });
+        assert!(found.is_none());
+        assert_eq!(calls.get(), rows.len());
+    }
+
+    fn row(language: &str, message: &str) -> Row {
+        Row {
+            language: language.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(row("French", "Bonjour").to_string(), "French: Bonjour");
+        assert_eq!(get_rows()[15].to_string(), "Polish: Dzień dobry");
+    }
+
+    #[test]
+    fn test_longest_greeting() {
+        let rows = get_rows();
+        assert_eq!(longest_greeting(&rows).unwrap().language, "Hindi");
+        assert!(longest_greeting(&[]).is_none());
+    }
+
+    #[test]
+    fn test_longest_greeting_ties() {
+        let rows = [row("Swedish", "God dag"), row("Norwegian", "God dag"), row("Spanish", "Hola")];
+        assert_eq!(longest_greeting(&rows).unwrap().language, "Norwegian");
+        // Characters, not bytes: "Olá" has 4 bytes but 3 characters
+        let rows = [row("Portuguese", "Olá"), row("Made up", "Abcd")];
+        assert_eq!(longest_greeting(&rows).unwrap().language, "Made up");
+    }
+
+    #[test]
+    fn test_index_has_every_language() {
+        let rows = get_rows();
+        assert_eq!(rows.len(), 22);
+        let greetings = Greetings::try_from(get_rows()).unwrap();
+        for row in rows.iter() {
+            assert_eq!(greetings.get(&row.language), Some(row.message.as_str()));
+        }
+        assert_eq!(greetings.get("japanese"), Some("Konnichiwa"));
+        assert_eq!(greetings.get("Klingon"), None);
+    }
+
+    #[test]
+    fn test_index_reports_duplicates() {
+        let mut rows = get_rows();
+        rows.push(Row {
+            language: "FRENCH".to_string(),
+            message: "Salut".to_string(),
+        });
+        assert_eq!(build_index(rows).unwrap_err(), DuplicateLanguage("FRENCH".to_string()));
+    }
+}
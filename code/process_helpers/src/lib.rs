@@ -0,0 +1,68 @@
+//! Helpers shared by the examples that launch other programs from this
+//! workspace.
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+mod spec;
+mod wait;
+pub use spec::{long_running_command, ProcessSpec};
+pub use wait::wait_with_timeout;
+
+/// Finds the executable `name`, looking first next to the running program
+/// (so `cargo run -p call_echo` finds the `echo` built into the same
+/// `target/debug`) and then on the `PATH`.
+///
+/// Test binaries live one level down in `target/<profile>/deps`, so the
+/// directory above that is checked too.
+pub fn find_sibling_binary(name: &str) -> Option<PathBuf> {
+    let exe_dir = env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf));
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    if let Some(dir) = exe_dir {
+        if dir.ends_with("deps") {
+            if let Some(parent) = dir.parent() {
+                dirs.push(parent.to_path_buf());
+            }
+        }
+        dirs.insert(0, dir);
+    }
+    if let Some(path) = env::var_os("PATH") {
+        dirs.extend(env::split_paths(&path));
+    }
+
+    dirs.iter().find_map(|dir| executable_in(dir, name))
+}
+
+fn executable_in(dir: &Path, name: &str) -> Option<PathBuf> {
+    [name.to_string(), format!("{name}{}", env::consts::EXE_SUFFIX)]
+        .into_iter()
+        .map(|file| dir.join(file))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_finds_own_directory() {
+        let exe = env::current_exe().unwrap();
+        let name = exe.file_name().unwrap().to_str().unwrap();
+        assert_eq!(find_sibling_binary(name), Some(exe.clone()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_falls_back_to_path() {
+        let sh = find_sibling_binary("sh").unwrap();
+        assert!(sh.is_absolute());
+        assert!(sh.ends_with("sh"));
+    }
+
+    #[test]
+    fn test_missing_binary() {
+        assert_eq!(find_sibling_binary("no-such-binary-anywhere"), None);
+    }
+}
@@ -0,0 +1,139 @@
+//! Describing a process to launch, so the examples set up their children in
+//! one place.
+
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::io;
+use std::path::PathBuf;
+use std::process::{Child, Command};
+
+/// Everything needed to launch a program: what to run, its arguments, any
+/// extra environment variables and the directory to run it in.
+///
+/// The child inherits this process's environment, plus whatever is in
+/// `env`. With no `cwd`, it runs in our current directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProcessSpec {
+    pub program: OsString,
+    pub args: Vec<OsString>,
+    pub env: HashMap<OsString, OsString>,
+    pub cwd: Option<PathBuf>,
+}
+
+impl ProcessSpec {
+    /// Starts a spec for running `program` with no arguments.
+    pub fn new(program: impl Into<OsString>) -> Self {
+        Self {
+            program: program.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Adds one argument.
+    pub fn arg(mut self, arg: impl Into<OsString>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Adds several arguments.
+    pub fn args<I>(mut self, args: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<OsString>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sets an environment variable for the child, replacing any earlier
+    /// value for `key`.
+    pub fn env(mut self, key: impl Into<OsString>, value: impl Into<OsString>) -> Self {
+        self.env.insert(key.into(), value.into());
+        self
+    }
+
+    /// Runs the child in `dir`.
+    pub fn cwd(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cwd = Some(dir.into());
+        self
+    }
+
+    /// Builds a [`Command`] from the spec. Use this when you need to set up
+    /// stdio or anything else the spec doesn't cover before spawning.
+    pub fn command(&self) -> Command {
+        let mut cmd = Command::new(&self.program);
+        cmd.args(&self.args).envs(&self.env);
+        if let Some(dir) = &self.cwd {
+            cmd.current_dir(dir);
+        }
+        cmd
+    }
+
+    /// Spawns the child, with stdio inherited from this process.
+    pub fn spawn(&self) -> io::Result<Child> {
+        self.command().spawn()
+    }
+}
+
+/// A command that keeps printing output until it's killed, without needing
+/// the network. Windows has no `sh`, so it gets a PowerShell loop instead.
+pub fn long_running_command() -> ProcessSpec {
+    if cfg!(target_os = "windows") {
+        ProcessSpec::new("powershell")
+            .args(["-NoProfile", "-Command", "while ($true) { Get-Date; Start-Sleep 1 }"])
+    } else {
+        ProcessSpec::new("sh").args(["-c", "while true; do date; sleep 1; done"])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_builder() {
+        let spec = ProcessSpec::new("thumbnailer")
+            .arg("in.jpg")
+            .args(["out.jpg", "--recursive"])
+            .env("SIZE", "100")
+            .cwd("/tmp");
+        assert_eq!(spec.program, "thumbnailer");
+        assert_eq!(spec.args, ["in.jpg", "out.jpg", "--recursive"]);
+        assert_eq!(spec.env.get(&OsString::from("SIZE")), Some(&OsString::from("100")));
+        assert_eq!(spec.cwd, Some(PathBuf::from("/tmp")));
+
+        let cmd = spec.command();
+        assert_eq!(cmd.get_program(), "thumbnailer");
+        assert_eq!(cmd.get_current_dir(), Some(std::path::Path::new("/tmp")));
+    }
+
+    #[test]
+    fn test_long_running_command() {
+        let spec = long_running_command();
+        if cfg!(target_os = "windows") {
+            assert_eq!(spec.program, "powershell");
+            assert_eq!(spec.args[..2], ["-NoProfile", "-Command"]);
+        } else {
+            assert_eq!(spec.program, "sh");
+            assert_eq!(spec.args[0], "-c");
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_child_sees_env() {
+        let mut child = ProcessSpec::new("sh")
+            .args(["-c", r#"test "$GREETING" = "hello there""#])
+            .env("GREETING", "hello there")
+            .spawn()
+            .unwrap();
+        assert!(child.wait().unwrap().success());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_child_runs_in_cwd() {
+        let output = ProcessSpec::new("pwd").cwd("/").command().output().unwrap();
+        assert_eq!(output.stdout, b"/\n");
+    }
+}
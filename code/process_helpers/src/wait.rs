@@ -0,0 +1,52 @@
+//! Waiting for a child process without blocking forever.
+
+use std::io;
+use std::process::{Child, ExitStatus};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long to sleep between checks on the child.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Waits up to `timeout` for `child` to exit. Returns `None` if it's still
+/// running, leaving it up to the caller whether to kill it.
+pub fn wait_with_timeout(child: &mut Child, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Ok(None);
+        }
+        thread::sleep(POLL_INTERVAL.min(deadline - now));
+    }
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use super::*;
+    use crate::ProcessSpec;
+
+    #[test]
+    fn test_fast_command_finishes() {
+        let mut child = ProcessSpec::new("true").spawn().unwrap();
+        let status = wait_with_timeout(&mut child, Duration::from_secs(5)).unwrap();
+        assert!(status.unwrap().success());
+    }
+
+    #[test]
+    fn test_slow_command_times_out() {
+        let mut child = ProcessSpec::new("sleep").arg("10").spawn().unwrap();
+        let started = Instant::now();
+        let status = wait_with_timeout(&mut child, Duration::from_millis(100)).unwrap();
+        assert!(status.is_none());
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        // Still running until we kill it
+        assert!(child.try_wait().unwrap().is_none());
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+}
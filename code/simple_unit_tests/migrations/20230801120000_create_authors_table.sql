@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS authors
+(
+    id          INTEGER PRIMARY KEY NOT NULL,
+    name        TEXT                NOT NULL
+);
+
+ALTER TABLE messages ADD COLUMN author_id INTEGER REFERENCES authors (id);
@@ -0,0 +1,2 @@
+INSERT INTO authors (id, name) VALUES (1, 'Herbert');
+INSERT INTO authors (id, name) VALUES (2, 'Ada');
\ No newline at end of file
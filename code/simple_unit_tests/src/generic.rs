@@ -0,0 +1,24 @@
+//! Generic versions of the math helpers, for any integer width.
+
+use num_traits::{CheckedMul, WrappingMul};
+
+/// Doubles `n`, wrapping around on overflow.
+pub fn double_overflow<T: WrappingMul + From<u8>>(n: T) -> T {
+    n.wrapping_mul(&T::from(2))
+}
+
+/// Doubles `n`, returning `None` on overflow.
+pub fn double_safe<T: CheckedMul + From<u8>>(n: T) -> Option<T> {
+    n.checked_mul(&T::from(2))
+}
+
+/// Triples `n`, returning `None` on overflow.
+///
+/// # Examples
+/// ```
+/// assert_eq!(simple_unit_tests::generic::triple(2u64), Some(6));
+/// assert_eq!(simple_unit_tests::generic::triple(i64::MAX), None);
+/// ```
+pub fn triple<T: CheckedMul + From<u8>>(n: T) -> Option<T> {
+    n.checked_mul(&T::from(3))
+}
@@ -1,34 +1,96 @@
+pub mod generic;
+pub mod messages;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+
 pub fn double_overflow(n: i32) -> i32 {
-    n.overflowing_mul(2).0
+    generic::double_overflow(n)
 }
 
 pub fn double_safe(n: i32) -> Result<i32, String> {
-    let (result, overflow) = n.overflowing_mul(2);
-    if overflow {
-        Err("overflow".to_string())
-    } else {
-        Ok(result)
-    }
+    generic::double_safe(n).ok_or_else(|| "overflow".to_string())
 }
 
 pub async fn async_double(n: i32) -> i32 {
     n * 2
 }
 
+/// Like [`async_double`], but waits for `delay` first.
+///
+/// It's cancellation safe: it holds no state, so dropping it part way
+/// through (for example when a `timeout` expires) loses nothing, and it can
+/// simply be called again.
+pub async fn async_double_delayed(n: i32, delay: std::time::Duration) -> i32 {
+    tokio::time::sleep(delay).await;
+    n * 2
+}
+
 use axum::{
+    body::Bytes,
+    http::{header, HeaderMap, StatusCode},
+    response::sse::{Event, Sse},
     routing::{get, post},
     Json, Router,
 };
+use std::convert::Infallible;
+use std::time::Duration;
+use tokio_stream::{Stream, StreamExt};
 
 pub fn app() -> Router {
     Router::new()
         .route("/", get(|| async { "Hello, World!" }))
+        .route("/json", post(echo_json))
+        .route("/health", get(|| async { "ok" }))
         .route(
-            "/json",
-            post(|payload: Json<serde_json::Value>| async move {
-                Json(serde_json::json!({ "data": payload.0 }))
-            }),
+            "/version",
+            get(|| async { Json(serde_json::json!({ "version": env!("CARGO_PKG_VERSION") })) }),
         )
+        .route("/stream", get(stream_counter))
+}
+
+/// How many events `/stream` sends before closing.
+pub const STREAM_EVENTS: usize = 5;
+
+/// How long `/stream` waits between events.
+pub const STREAM_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Sends server-sent events counting from 1 to [`STREAM_EVENTS`], one every
+/// [`STREAM_INTERVAL`], then ends the response.
+async fn stream_counter() -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let ticks = tokio_stream::wrappers::IntervalStream::new(tokio::time::interval(STREAM_INTERVAL));
+    let mut count = 0;
+    let events = ticks.take(STREAM_EVENTS).map(move |_| {
+        count += 1;
+        Ok(Event::default().data(count.to_string()))
+    });
+    Sse::new(events)
+}
+
+/// Echoes the posted JSON array back under `data`, along with the request's
+/// `Content-Type` (or `null` if it didn't send one). Anything other than an
+/// array is rejected with `422 Unprocessable Entity`.
+///
+/// The body is parsed by hand rather than with the `Json` extractor, because
+/// `Json` rejects requests that don't declare a JSON content type.
+async fn echo_json(
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let payload: serde_json::Value = serde_json::from_slice(&body)
+        .map_err(|err| (StatusCode::BAD_REQUEST, format!("Invalid JSON: {err}")))?;
+    if !payload.is_array() {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Expected a JSON array".to_string(),
+        ));
+    }
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok());
+    Ok(Json(serde_json::json!({
+        "data": payload,
+        "content_type": content_type,
+    })))
 }
 
 /// Triple a number
@@ -39,22 +101,49 @@ pub fn app() -> Router {
 /// # Returns
 /// The tripled number
 /// 
+/// # Panics
+/// Panics if the result overflows an `i32`. Use [`generic::triple`] to
+/// detect overflow instead.
+/// 
 /// # Examples
 /// ```
 /// assert_eq!(simple_unit_tests::triple(2), 6);
 /// ```
 pub fn triple(n: i32) -> i32 {
-    n * 3
+    generic::triple(n).expect("overflow")
 }
 
-#[cfg(not(test))]
-pub fn complex_math() -> i32 {
-    4 * 3 // Let's pretend that's complex
+/// Something that can perform our "complex" calculation. Injecting a
+/// provider lets tests swap in a stub without `#[cfg(test)]` tricks.
+pub trait MathProvider {
+    fn compute(&self) -> i32;
+}
+
+/// The production implementation.
+pub struct RealMath;
+
+impl MathProvider for RealMath {
+    fn compute(&self) -> i32 {
+        4 * 3 // Let's pretend that's complex
+    }
+}
+
+/// A stub that skips the calculation and returns a canned answer.
+pub struct StubMath(pub i32);
+
+impl MathProvider for StubMath {
+    fn compute(&self) -> i32 {
+        self.0
+    }
+}
+
+/// Runs the calculation with the given provider.
+pub fn complex_math_with(provider: &impl MathProvider) -> i32 {
+    provider.compute()
 }
 
-#[cfg(test)]
 pub fn complex_math() -> i32 {
-    12
+    complex_math_with(&RealMath)
 }
 
 #[cfg(not(test))]
@@ -68,10 +157,76 @@ use mockall::*;
 #[automock]
 pub trait MyTrait {
     fn calculate(&self, x: u32) -> u32;
+    fn step(&mut self, n: u32);
+}
+
+/// An async dependency. `async fn` in traits needs `async_trait` for
+/// mockall to generate `MockAsyncCalculator`; `automock` must come first.
+#[automock]
+#[async_trait::async_trait]
+pub trait AsyncCalculator {
+    async fn calculate(&self, x: u32) -> u32;
+}
+
+/// Adds the results of calculating `a` and `b`.
+pub async fn sum_calculations(calculator: &impl AsyncCalculator, a: u32, b: u32) -> u32 {
+    calculator.calculate(a).await + calculator.calculate(b).await
+}
+
+/// Calls `step` with 1, 2, ... up to `count`, in order.
+pub fn run_steps(target: &mut impl MyTrait, count: u32) {
+    for n in 1..=count {
+        target.step(n);
+    }
+}
+
+/// Reasons an email address can fail validation.
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum EmailError {
+    #[error("Email address is empty")]
+    Empty,
+    #[error("Email address has no '@'")]
+    MissingAt,
+    #[error("Email address has more than one '@'")]
+    MultipleAt,
+    #[error("Nothing before the '@'")]
+    EmptyLocal,
+    #[error("Nothing after the '@'")]
+    EmptyDomain,
+    #[error("Domain has no '.'")]
+    MissingDomainDot,
+    #[error("Domain has an empty label, such as a leading or doubled '.'")]
+    EmptyDomainLabel,
+}
+
+/// Checks that an email address is structurally plausible: exactly one `@`,
+/// a non-empty local part, and a domain made of at least two non-empty,
+/// dot-separated labels. Returns the first problem found.
+pub fn validate_email(email: &str) -> Result<(), EmailError> {
+    if email.is_empty() {
+        return Err(EmailError::Empty);
+    }
+    let (local, domain) = email.split_once('@').ok_or(EmailError::MissingAt)?;
+    if domain.contains('@') {
+        return Err(EmailError::MultipleAt);
+    }
+    if local.is_empty() {
+        return Err(EmailError::EmptyLocal);
+    }
+    if domain.is_empty() {
+        return Err(EmailError::EmptyDomain);
+    }
+    if !domain.contains('.') {
+        return Err(EmailError::MissingDomainDot);
+    }
+    if domain.split('.').any(|label| label.is_empty()) {
+        return Err(EmailError::EmptyDomainLabel);
+    }
+    Ok(())
 }
 
 pub fn is_email_valid(email: &str) -> bool {
-    !email.is_empty() && email.contains('@')
+    validate_email(email).is_ok()
 }
 
 #[cfg(test)]
@@ -109,37 +264,156 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_generic_double_overflow() {
+        assert_eq!(generic::double_overflow(5i64), 10);
+        assert_eq!(generic::double_overflow(i64::MAX), -2);
+        assert_eq!(generic::double_overflow(u64::MAX), u64::MAX - 1);
+    }
+
+    #[test]
+    fn test_generic_double_safe() {
+        assert_eq!(generic::double_safe(5i64), Some(10));
+        assert_eq!(generic::double_safe(i64::MAX), None);
+        assert_eq!(generic::double_safe(u64::MAX / 2), Some(u64::MAX - 1));
+        assert_eq!(generic::double_safe(u64::MAX), None);
+    }
+
+    #[test]
+    fn test_generic_triple() {
+        assert_eq!(generic::triple(4i64), Some(12));
+        assert_eq!(generic::triple(i64::MAX), None);
+        assert_eq!(generic::triple(i64::MAX / 3), Some(i64::MAX / 3 * 3));
+        assert_eq!(generic::triple(u64::MAX), None);
+    }
+
+    #[test]
+    fn test_i32_wrappers() {
+        assert_eq!(triple(2), 6);
+        assert_eq!(double_safe(i32::MAX), Err("overflow".to_string()));
+        assert_eq!(double_overflow(i32::MAX), -2);
+    }
+
     #[tokio::test]
     async fn test_async_double() {
         assert_eq!(4, async_double(2).await);
     }
 
+    #[tokio::test]
+    async fn test_async_double_delayed_cancelled() {
+        use std::time::Duration;
+        let result = tokio::time::timeout(
+            Duration::from_millis(10),
+            async_double_delayed(2, Duration::from_secs(10)),
+        )
+        .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_async_double_delayed_paused() {
+        use std::time::Duration;
+        use tokio::time::{timeout, Instant};
+
+        // With the clock paused, tokio jumps time forward whenever
+        // everything is waiting, so this takes no real time at all.
+        let start = Instant::now();
+        assert_eq!(async_double_delayed(21, Duration::from_secs(60)).await, 42);
+        assert_eq!(start.elapsed(), Duration::from_secs(60));
+
+        // Not done after 59 seconds, done by 61
+        let mut doubling = std::pin::pin!(async_double_delayed(21, Duration::from_secs(60)));
+        assert!(timeout(Duration::from_secs(59), &mut doubling).await.is_err());
+        assert_eq!(timeout(Duration::from_secs(2), doubling).await, Ok(42));
+    }
+
     #[sqlx::test]
     async fn test_insert(pool: sqlx::SqlitePool) -> sqlx::Result<()> {
-        use sqlx::{Executor, Row};
-        let mut conn = pool.acquire().await?;
+        use messages::{get_message, insert_message, Message};
 
-        conn.execute("INSERT INTO messages (id, message) VALUES (1, 'Hello')")
-            .await?;
+        let id = insert_message(&pool, "Hello").await?;
 
-        let rows = sqlx::query("SELECT * FROM messages")
-            .fetch_all(&pool)
-            .await?;
-        assert_eq!(rows.len(), 1);
-        assert_eq!(rows[0].get::<i64, _>("id"), 1);
-        assert_eq!(rows[0].get::<String, _>("message"), "Hello");
+        assert_eq!(
+            get_message(&pool, id).await?,
+            Some(Message {
+                id,
+                message: "Hello".to_string()
+            })
+        );
         Ok(())
     }
 
     #[sqlx::test(fixtures("some_messages"))]
     async fn test_fixture(pool: sqlx::SqlitePool) -> sqlx::Result<()> {
+        let message = messages::get_message(&pool, 1).await?.unwrap();
+        assert_eq!(message.id, 1);
+        assert_eq!(message.message, "Hello World!");
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("some_messages"))]
+    async fn test_missing_message(pool: sqlx::SqlitePool) -> sqlx::Result<()> {
+        assert_eq!(messages::get_message(&pool, 42).await?, None);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("some_messages"))]
+    async fn test_insert_after_fixture(pool: sqlx::SqlitePool) -> sqlx::Result<()> {
+        let id = messages::insert_message(&pool, "Hello Multiverse!").await?;
+        assert_eq!(id, 4);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("authors"))]
+    async fn test_join(pool: sqlx::SqlitePool) -> sqlx::Result<()> {
         use sqlx::Row;
-        let rows = sqlx::query("SELECT * FROM messages WHERE id=1")
-            .fetch_all(&pool)
+        sqlx::query("INSERT INTO authors (id, name) VALUES (3, 'Grace')")
+            .execute(&pool)
             .await?;
+        sqlx::query("INSERT INTO messages (id, message, author_id) VALUES (1, 'Hello', 3)")
+            .execute(&pool)
+            .await?;
+
+        let rows = sqlx::query(
+            "SELECT messages.message, authors.name FROM messages \
+             JOIN authors ON authors.id = messages.author_id",
+        )
+        .fetch_all(&pool)
+        .await?;
         assert_eq!(rows.len(), 1);
-        assert_eq!(rows[0].get::<i64, _>("id"), 1);
-        assert_eq!(rows[0].get::<String, _>("message"), "Hello World!");
+        assert_eq!(rows[0].get::<String, _>("message"), "Hello");
+        assert_eq!(rows[0].get::<String, _>("name"), "Grace");
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("authors", "some_messages"))]
+    async fn test_fixtures_together(pool: sqlx::SqlitePool) -> sqlx::Result<()> {
+        use sqlx::Row;
+        sqlx::query("UPDATE messages SET author_id = 2 WHERE id = 3")
+            .execute(&pool)
+            .await?;
+
+        let row = sqlx::query(
+            "SELECT authors.name, COUNT(messages.id) AS count FROM authors \
+             LEFT JOIN messages ON authors.id = messages.author_id \
+             GROUP BY authors.id ORDER BY authors.id",
+        )
+        .fetch_all(&pool)
+        .await?;
+        assert_eq!(row.len(), 2);
+        assert_eq!(row[0].get::<String, _>("name"), "Herbert");
+        assert_eq!(row[0].get::<i64, _>("count"), 0);
+        assert_eq!(row[1].get::<String, _>("name"), "Ada");
+        assert_eq!(row[1].get::<i64, _>("count"), 1);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("authors"))]
+    async fn test_author_must_exist(pool: sqlx::SqlitePool) -> sqlx::Result<()> {
+        let result = sqlx::query("INSERT INTO messages (id, message, author_id) VALUES (1, 'Hello', 99)")
+            .execute(&pool)
+            .await;
+        assert!(result.is_err());
         Ok(())
     }
 
@@ -188,7 +462,163 @@ mod test {
 
         let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
         let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
-        assert_eq!(body, serde_json::json!({ "data": [1, 2, 3, 4] }));
+        assert_eq!(
+            body,
+            serde_json::json!({ "data": [1, 2, 3, 4], "content_type": "application/json" })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_json_without_content_type() {
+        let app = app();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/json")
+                    .body(Body::from(
+                        serde_json::to_vec(&serde_json::json!([1, 2, 3, 4])).unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body, serde_json::json!({ "data": [1, 2, 3, 4], "content_type": null }));
+    }
+
+    #[tokio::test]
+    async fn test_json_array() {
+        let app = app();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/json")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .body(Body::from(
+                        serde_json::to_vec(&serde_json::json!([1, 2, 3])).unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_json_rejects_non_array() {
+        let app = app();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/json")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .body(Body::from(
+                        serde_json::to_vec(&serde_json::json!({ "a": 1 })).unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(&body[..], b"Expected a JSON array");
+    }
+
+    #[tokio::test]
+    async fn test_json_invalid_body() {
+        let app = app();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/json")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .body(Body::from("not json"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_health() {
+        let app = app();
+
+        let response = app
+            .oneshot(Request::builder().uri("/health").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(&body[..], b"ok");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stream() {
+        let app = app();
+
+        let response = app
+            .oneshot(Request::builder().uri("/stream").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[http::header::CONTENT_TYPE],
+            mime::TEXT_EVENT_STREAM.as_ref()
+        );
+
+        // The stream ends by itself, so the whole body can be collected
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        let data: Vec<&str> = body.lines().filter(|line| line.starts_with("data:")).collect();
+        assert_eq!(data, ["data:1", "data:2", "data:3", "data:4", "data:5"]);
+    }
+
+    #[tokio::test]
+    async fn test_version() {
+        let app = app();
+
+        let response = app
+            .oneshot(Request::builder().uri("/version").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body, serde_json::json!({ "version": env!("CARGO_PKG_VERSION") }));
+    }
+
+    #[test]
+    fn test_real_math() {
+        assert_eq!(RealMath.compute(), 12);
+        assert_eq!(complex_math(), 12);
+    }
+
+    #[test]
+    fn test_stub_math() {
+        assert_eq!(StubMath(7).compute(), 7);
+        assert_eq!(complex_math_with(&StubMath(99)), 99);
     }
 
     struct StubMe;
@@ -239,6 +669,58 @@ mod test {
         assert_eq!(mock.calculate(4), 35);
     }
 
+    #[test]
+    fn test_my_mock_sequence() {
+        let mut mock = MockMyTrait::new();
+        let mut seq = Sequence::new();
+        // `step` must be called exactly twice: first with 1, then with 2
+        mock.expect_step()
+            .with(eq(1))
+            .times(1)
+            .in_sequence(&mut seq)
+            .return_const(());
+        mock.expect_step()
+            .with(eq(2))
+            .times(1)
+            .in_sequence(&mut seq)
+            .return_const(());
+        run_steps(&mut mock, 2);
+        // Expectations are checked when the mock is dropped
+    }
+
+    #[test]
+    fn test_my_mock_times() {
+        let mut mock = MockMyTrait::new();
+        mock.expect_step().times(3).return_const(());
+        run_steps(&mut mock, 3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_my_mock_sequence_out_of_order() {
+        let mut mock = MockMyTrait::new();
+        let mut seq = Sequence::new();
+        mock.expect_step()
+            .with(eq(1))
+            .times(1)
+            .in_sequence(&mut seq)
+            .return_const(());
+        mock.expect_step()
+            .with(eq(2))
+            .times(1)
+            .in_sequence(&mut seq)
+            .return_const(());
+        mock.step(2);
+    }
+
+    #[tokio::test]
+    async fn test_async_mock() {
+        let mut mock = MockAsyncCalculator::new();
+        mock.expect_calculate().returning(|x| x * 10);
+        assert_eq!(mock.calculate(4).await, 40);
+        assert_eq!(sum_calculations(&mock, 1, 2).await, 30);
+    }
+
     #[test]
     fn test_random_email() {
         use fake::faker::internet::en::SafeEmail;
@@ -249,23 +731,57 @@ mod test {
         assert!(is_email_valid(&email));
     }
 
-    // Both `Clone` and `Debug` are required by `quickcheck`
-   #[derive(Debug, Clone)]
-   struct ValidEmailFixture(pub String);
+    #[test]
+    fn test_valid_emails() {
+        assert!(is_email_valid("herbert@example.com"));
+        assert!(is_email_valid("a.b@mail.example.co.uk"));
+    }
 
-   use fake::faker::internet::en::SafeEmail;
-   use fake::Fake;
+    #[test]
+    fn test_invalid_emails() {
+        assert!(!is_email_valid(""));
+        assert!(!is_email_valid("@"));
+        assert!(!is_email_valid("a@@b.com"));
+        assert!(!is_email_valid("a@b"));
+        assert!(!is_email_valid("a@.com"));
+        assert!(!is_email_valid("a@b."));
+        assert!(!is_email_valid("@b.com"));
+        assert!(!is_email_valid("a@b@c.com"));
+    }
 
-   impl quickcheck::Arbitrary for ValidEmailFixture {
-      fn arbitrary(g: &mut quickcheck::Gen) -> Self {
-         let email = SafeEmail().fake();
-         Self(email)
-      }
-   }
+    #[test]
+    fn test_email_errors() {
+        assert_eq!(validate_email(""), Err(EmailError::Empty));
+        assert_eq!(validate_email("herbert"), Err(EmailError::MissingAt));
+        assert_eq!(validate_email("a@@b.com"), Err(EmailError::MultipleAt));
+        assert_eq!(validate_email("a@b@c.com"), Err(EmailError::MultipleAt));
+        assert_eq!(validate_email("@b.com"), Err(EmailError::EmptyLocal));
+        assert_eq!(validate_email("a@"), Err(EmailError::EmptyDomain));
+        assert_eq!(validate_email("a@b"), Err(EmailError::MissingDomainDot));
+        assert_eq!(validate_email("a@.com"), Err(EmailError::EmptyDomainLabel));
+        assert_eq!(validate_email("a@b..com"), Err(EmailError::EmptyDomainLabel));
+        assert_eq!(validate_email("a@b.com"), Ok(()));
+    }
+
+   use testing::{InvalidEmailFixture, ValidEmailFixture};
 
    #[quickcheck_macros::quickcheck]
    fn valid_emails_are_parsed_successfully(valid_email: ValidEmailFixture) -> bool {
       is_email_valid(&valid_email.0)
    }
+
+   #[quickcheck_macros::quickcheck]
+   fn invalid_emails_are_rejected(invalid_email: InvalidEmailFixture) -> bool {
+      !is_email_valid(&invalid_email.0)
+   }
+
+   #[test]
+   fn test_fixture_samples() {
+      assert!(is_email_valid(&ValidEmailFixture::sample()));
+      for _ in 0..100 {
+         let email = InvalidEmailFixture::sample();
+         assert!(!is_email_valid(&email), "{email}");
+      }
+   }
 }
 
@@ -0,0 +1,27 @@
+//! Typed access to the `messages` table, so callers (and tests) don't have
+//! to repeat SQL strings and look up columns by name.
+
+use sqlx::SqlitePool;
+
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct Message {
+    pub id: i64,
+    pub message: String,
+}
+
+/// Stores a new message, returning its id.
+pub async fn insert_message(pool: &SqlitePool, message: &str) -> sqlx::Result<i64> {
+    let result = sqlx::query("INSERT INTO messages (message) VALUES (?)")
+        .bind(message)
+        .execute(pool)
+        .await?;
+    Ok(result.last_insert_rowid())
+}
+
+/// Fetches the message with the given id, or `None` if there isn't one.
+pub async fn get_message(pool: &SqlitePool, id: i64) -> sqlx::Result<Option<Message>> {
+    sqlx::query_as::<_, Message>("SELECT id, message FROM messages WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
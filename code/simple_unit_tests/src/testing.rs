@@ -0,0 +1,61 @@
+//! Fixtures for property tests of email validation. Enable the `testing`
+//! feature to use them from another crate's tests.
+
+use fake::faker::internet::en::SafeEmail;
+use fake::Fake;
+
+/// An email address that [`is_email_valid`](crate::is_email_valid) accepts.
+// Both `Clone` and `Debug` are required by `quickcheck`
+#[derive(Debug, Clone)]
+pub struct ValidEmailFixture(pub String);
+
+impl ValidEmailFixture {
+    /// A random valid address.
+    pub fn sample() -> String {
+        SafeEmail().fake()
+    }
+}
+
+impl quickcheck::Arbitrary for ValidEmailFixture {
+    fn arbitrary(_g: &mut quickcheck::Gen) -> Self {
+        Self(Self::sample())
+    }
+}
+
+/// An email address that [`is_email_valid`](crate::is_email_valid) rejects.
+#[derive(Debug, Clone)]
+pub struct InvalidEmailFixture(pub String);
+
+/// Ways of breaking a valid address.
+const BREAKAGES: [fn(&str, &str) -> String; 8] = [
+    |_, _| String::new(),
+    |local, domain| format!("{local}{domain}"),
+    |local, domain| format!("{local}@@{domain}"),
+    |_, domain| format!("@{domain}"),
+    |local, _| format!("{local}@"),
+    |local, domain| format!("{local}@{}", domain.replace('.', "")),
+    |local, domain| format!("{local}@{domain}."),
+    |local, domain| format!("{local}@.{domain}"),
+];
+
+impl InvalidEmailFixture {
+    /// A random invalid address, made by breaking a valid one in one of
+    /// several ways.
+    pub fn sample() -> String {
+        let mut g = quickcheck::Gen::new(BREAKAGES.len());
+        Self::broken(&mut g)
+    }
+
+    fn broken(g: &mut quickcheck::Gen) -> String {
+        let valid = ValidEmailFixture::sample();
+        let (local, domain) = valid.split_once('@').expect("valid emails have an '@'");
+        let breakage = g.choose(&BREAKAGES).expect("BREAKAGES isn't empty");
+        breakage(local, domain)
+    }
+}
+
+impl quickcheck::Arbitrary for InvalidEmailFixture {
+    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+        Self(Self::broken(g))
+    }
+}
@@ -0,0 +1,903 @@
+use anyhow::{anyhow, bail, Context};
+use image::io::Reader;
+use image::{DynamicImage, ImageFormat};
+use rayon::prelude::*;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use std::time::Instant;
+
+/// The bounding box used when the caller doesn't pick one.
+pub const DEFAULT_SIZE: u32 = 100;
+
+/// The largest image (in pixels) we're willing to decode. A few bytes of
+/// header can claim enormous dimensions, and decoding would then try to
+/// allocate all of them.
+pub const MAX_PIXELS: u64 = 100_000_000;
+
+/// Reads the image at `image_path` and writes a thumbnail of it to
+/// `thumbnail_path`. The thumbnail keeps the image's aspect ratio and fits
+/// within `max_width` x `max_height`.
+///
+/// The thumbnail is written as `format`, or if that's `None`, in the format
+/// matching the extension of `thumbnail_path`.
+///
+/// Each call runs in a `make_thumbnail` span recording the paths, the
+/// source image's dimensions and how long the job took, ending with an info
+/// event on success or an error event on failure.
+#[tracing::instrument(
+    skip_all,
+    err(Display),
+    fields(
+        input = %image_path.as_ref().display(),
+        output = %thumbnail_path.as_ref().display(),
+        source_width = tracing::field::Empty,
+        source_height = tracing::field::Empty,
+        elapsed_ms = tracing::field::Empty,
+    )
+)]
+pub fn make_thumbnail(
+    image_path: impl AsRef<Path>,
+    thumbnail_path: impl AsRef<Path>,
+    max_width: u32,
+    max_height: u32,
+    format: Option<ImageFormat>,
+) -> anyhow::Result<()> {
+    let started = Instant::now();
+    let result = write_thumbnail(
+        image_path.as_ref(),
+        thumbnail_path.as_ref(),
+        max_width,
+        max_height,
+        format,
+    );
+    tracing::Span::current().record("elapsed_ms", started.elapsed().as_millis() as u64);
+    if result.is_ok() {
+        tracing::info!("Thumbnail created");
+    }
+    result
+}
+
+fn write_thumbnail(
+    image_path: &Path,
+    thumbnail_path: &Path,
+    max_width: u32,
+    max_height: u32,
+    format: Option<ImageFormat>,
+) -> anyhow::Result<()> {
+    let format = output_format(thumbnail_path, format)?;
+
+    let image_bytes: Vec<u8> = std::fs::read(image_path)?;
+    let image = decode(&image_bytes)?;
+    let span = tracing::Span::current();
+    span.record("source_width", image.width());
+    span.record("source_height", image.height());
+    let thumbnail = image.thumbnail(max_width, max_height);
+    thumbnail
+        .save_with_format(thumbnail_path, format)
+        .with_context(|| {
+            format!(
+                "Unable to write {format:?} thumbnail {}",
+                thumbnail_path.display()
+            )
+        })?;
+    Ok(())
+}
+
+/// Picks the format to write `thumbnail_path` in: `format` if given,
+/// otherwise whatever its extension implies.
+fn output_format(thumbnail_path: &Path, format: Option<ImageFormat>) -> anyhow::Result<ImageFormat> {
+    let format = match format {
+        Some(format) => format,
+        None => ImageFormat::from_path(thumbnail_path).map_err(|_| {
+            anyhow!(
+                "Can't tell which image format to use for {}; use a known extension or choose a format",
+                thumbnail_path.display()
+            )
+        })?,
+    };
+    check_encoder(format)?;
+    Ok(format)
+}
+
+/// Fails unless thumbnails can be encoded as `format` in this build.
+fn check_encoder(format: ImageFormat) -> anyhow::Result<()> {
+    if !format.can_write() {
+        bail!("Thumbnails can't be written as {format:?}");
+    }
+    // `image` claims it can write these, but only has an encoder if the
+    // matching feature is compiled in
+    if format == ImageFormat::WebP && !cfg!(feature = "webp") {
+        bail!("WebP thumbnails need thumbnailer's `webp` feature");
+    }
+    if format == ImageFormat::Avif && !cfg!(feature = "avif") {
+        bail!("AVIF thumbnails need thumbnailer's `avif` feature");
+    }
+    Ok(())
+}
+
+/// Formats suited to thumbnails for the web. WebP and AVIF are usually much
+/// smaller than PNG or JPEG, but need the `webp` (on by default) and `avif`
+/// features respectively.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThumbnailFormat {
+    Png,
+    Jpeg,
+    WebP,
+    Avif,
+}
+
+impl ThumbnailFormat {
+    /// Whether this build can encode thumbnails in this format.
+    pub fn is_available(self) -> bool {
+        check_encoder(self.into()).is_ok()
+    }
+
+    /// The MIME type to serve thumbnails in this format with.
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            ThumbnailFormat::Png => "image/png",
+            ThumbnailFormat::Jpeg => "image/jpeg",
+            ThumbnailFormat::WebP => "image/webp",
+            ThumbnailFormat::Avif => "image/avif",
+        }
+    }
+}
+
+impl TryFrom<ImageFormat> for ThumbnailFormat {
+    type Error = anyhow::Error;
+
+    fn try_from(format: ImageFormat) -> anyhow::Result<Self> {
+        match format {
+            ImageFormat::Png => Ok(ThumbnailFormat::Png),
+            ImageFormat::Jpeg => Ok(ThumbnailFormat::Jpeg),
+            ImageFormat::WebP => Ok(ThumbnailFormat::WebP),
+            ImageFormat::Avif => Ok(ThumbnailFormat::Avif),
+            other => bail!("{other:?} isn't a thumbnail format"),
+        }
+    }
+}
+
+impl From<ThumbnailFormat> for ImageFormat {
+    fn from(format: ThumbnailFormat) -> Self {
+        match format {
+            ThumbnailFormat::Png => ImageFormat::Png,
+            ThumbnailFormat::Jpeg => ImageFormat::Jpeg,
+            ThumbnailFormat::WebP => ImageFormat::WebP,
+            ThumbnailFormat::Avif => ImageFormat::Avif,
+        }
+    }
+}
+
+/// The most [`thumbnail_from_url`] will download, in bytes.
+pub const MAX_DOWNLOAD_BYTES: usize = 20 * 1024 * 1024;
+
+/// Downloads the image at `url` and writes a thumbnail of it to `out`, in
+/// the format matching `out`'s extension.
+///
+/// The response must have an `image/*` content type and be no more than
+/// [`MAX_DOWNLOAD_BYTES`] long. Decoding happens on a blocking thread, so
+/// this is safe to call from async code.
+pub async fn thumbnail_from_url(
+    url: &str,
+    out: &Path,
+    max_width: u32,
+    max_height: u32,
+) -> anyhow::Result<()> {
+    let format = output_format(out, None)?;
+    let image_bytes = fetch_image(url, MAX_DOWNLOAD_BYTES).await?;
+    let thumbnail = tokio::task::spawn_blocking(move || {
+        thumbnail_bytes(&image_bytes, max_width, max_height, format)
+    })
+    .await??;
+    tokio::fs::write(out, thumbnail)
+        .await
+        .with_context(|| format!("Unable to write thumbnail {}", out.display()))?;
+    Ok(())
+}
+
+/// Downloads an image, refusing anything that isn't `image/*` or is larger
+/// than `limit` bytes. The limit is checked as the body arrives, so a
+/// server that lies about (or omits) its `Content-Length` can't get past it.
+async fn fetch_image(url: &str, limit: usize) -> anyhow::Result<Vec<u8>> {
+    let mut response = reqwest::get(url)
+        .await
+        .with_context(|| format!("Unable to fetch {url}"))?
+        .error_for_status()?;
+
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if !content_type.starts_with("image/") {
+        bail!("{url} isn't an image (content type {content_type:?})");
+    }
+    if response.content_length().is_some_and(|len| len > limit as u64) {
+        bail!("{url} is larger than the limit of {limit} bytes");
+    }
+
+    let mut image_bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if image_bytes.len() + chunk.len() > limit {
+            bail!("{url} is larger than the limit of {limit} bytes");
+        }
+        image_bytes.extend_from_slice(&chunk);
+    }
+    Ok(image_bytes)
+}
+
+/// Makes a thumbnail of an encoded image held in memory, returning the
+/// thumbnail encoded as `format`. Nothing touches the filesystem.
+pub fn thumbnail_bytes(
+    image_bytes: &[u8],
+    max_width: u32,
+    max_height: u32,
+    format: ImageFormat,
+) -> anyhow::Result<Vec<u8>> {
+    check_encoder(format)?;
+    let thumbnail = decode(image_bytes)?.thumbnail(max_width, max_height);
+    let mut encoded = Vec::new();
+    thumbnail
+        .write_to(&mut Cursor::new(&mut encoded), format)
+        .with_context(|| format!("Unable to encode {format:?} thumbnail"))?;
+    Ok(encoded)
+}
+
+/// Makes a thumbnail of an encoded image for serving over the web,
+/// returning it along with the format it was encoded in.
+///
+/// With no `format`, the thumbnail matches the original if that's a
+/// [`ThumbnailFormat`] this build can write, and is a PNG otherwise.
+pub fn web_thumbnail(
+    image_bytes: &[u8],
+    max_width: u32,
+    max_height: u32,
+    format: Option<ThumbnailFormat>,
+) -> anyhow::Result<(Vec<u8>, ThumbnailFormat)> {
+    let format = format.unwrap_or_else(|| {
+        image::guess_format(image_bytes)
+            .ok()
+            .and_then(|format| ThumbnailFormat::try_from(format).ok())
+            .filter(|format| format.is_available())
+            .unwrap_or(ThumbnailFormat::Png)
+    });
+    let thumbnail = thumbnail_bytes(image_bytes, max_width, max_height, format.into())?;
+    Ok((thumbnail, format))
+}
+
+fn decode(image_bytes: &[u8]) -> anyhow::Result<DynamicImage> {
+    decode_with_limit(image_bytes, MAX_PIXELS)
+}
+
+/// Decodes an image, first reading just its dimensions from the header and
+/// refusing it if it has more than `max_pixels` pixels.
+fn decode_with_limit(image_bytes: &[u8], max_pixels: u64) -> anyhow::Result<DynamicImage> {
+    let reader = match image::guess_format(image_bytes) {
+        Ok(format) => Reader::with_format(Cursor::new(image_bytes), format),
+        Err(_) => Reader::new(Cursor::new(image_bytes)).with_guessed_format()?,
+    };
+    let (width, height) = reader.into_dimensions()?;
+    if u64::from(width) * u64::from(height) > max_pixels {
+        bail!("Image is {width}x{height}, more than the limit of {max_pixels} pixels");
+    }
+
+    let image = if let Ok(format) = image::guess_format(image_bytes) {
+        image::load_from_memory_with_format(image_bytes, format)?
+    } else {
+        image::load_from_memory(image_bytes)?
+    };
+    Ok(apply_orientation(image, exif_orientation(image_bytes)))
+}
+
+/// Reads the EXIF orientation tag (1-8), if the image has one.
+fn exif_orientation(image_bytes: &[u8]) -> Option<u32> {
+    let exif = exif::Reader::new()
+        .read_from_container(&mut Cursor::new(image_bytes))
+        .ok()?;
+    exif.get_field(exif::Tag::Orientation, exif::In::PRIMARY)?
+        .value
+        .get_uint(0)
+}
+
+/// Rotates and flips `image` so it displays upright, undoing what the camera
+/// recorded in the EXIF orientation tag.
+fn apply_orientation(image: DynamicImage, orientation: Option<u32>) -> DynamicImage {
+    match orientation {
+        Some(2) => image.fliph(),
+        Some(3) => image.rotate180(),
+        Some(4) => image.flipv(),
+        Some(5) => image.rotate90().fliph(),
+        Some(6) => image.rotate90(),
+        Some(7) => image.rotate270().fliph(),
+        Some(8) => image.rotate270(),
+        _ => image,
+    }
+}
+
+/// Makes a thumbnail, fitting within `max_width` x `max_height`, for each
+/// `(image, thumbnail)` pair in parallel.
+///
+/// Every input gets its own result, in the same order as `inputs`, so one
+/// bad image doesn't stop the rest of the batch.
+pub fn make_thumbnails(
+    inputs: &[(PathBuf, PathBuf)],
+    max_width: u32,
+    max_height: u32,
+) -> Vec<(PathBuf, anyhow::Result<()>)> {
+    inputs
+        .par_iter()
+        .map(|(image_path, thumbnail_path)| {
+            let result = make_thumbnail(image_path, thumbnail_path, max_width, max_height, None);
+            (image_path.clone(), result)
+        })
+        .collect()
+}
+
+/// Thumbnails every image under `src`, including in subdirectories, writing
+/// each thumbnail to the same relative path under `dst`.
+///
+/// Files are recognized as images by their extension; anything else is
+/// skipped. Each image gets its own result, keyed by its path under `src`,
+/// so one bad image doesn't stop the rest. The whole call only fails if
+/// the directories can't be read or created.
+pub fn thumbnail_directory(
+    src: &Path,
+    dst: &Path,
+    max_width: u32,
+    max_height: u32,
+) -> anyhow::Result<Vec<(PathBuf, Result<(), String>)>> {
+    // If `dst` is inside `src`, don't thumbnail the thumbnails from last time
+    let skip = dst.canonicalize().ok();
+    let mut images = Vec::new();
+    find_images(src, skip.as_deref(), &mut images)?;
+
+    let mut jobs = Vec::with_capacity(images.len());
+    for image_path in images {
+        let relative = image_path.strip_prefix(src)?;
+        let thumbnail_path = dst.join(relative);
+        if let Some(parent) = thumbnail_path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Unable to create {}", parent.display()))?;
+        }
+        jobs.push((image_path, thumbnail_path));
+    }
+
+    Ok(make_thumbnails(&jobs, max_width, max_height)
+        .into_iter()
+        .map(|(path, result)| (path, result.map_err(|err| format!("{err:#}"))))
+        .collect())
+}
+
+/// Adds every file under `dir` with an image extension to `images`, in a
+/// stable (sorted) order, skipping the directory `skip`.
+fn find_images(dir: &Path, skip: Option<&Path>, images: &mut Vec<PathBuf>) -> anyhow::Result<()> {
+    let mut entries = std::fs::read_dir(dir)
+        .with_context(|| format!("Unable to read {}", dir.display()))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            if skip.is_some_and(|skip| path.canonicalize().is_ok_and(|path| path == skip)) {
+                continue;
+            }
+            find_images(&path, skip, images)?;
+        } else if ImageFormat::from_path(&path).is_ok() {
+            images.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Progress reported by [`make_thumbnails_with_progress`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ThumbnailEvent {
+    /// Work on the image at this path has begun.
+    Started(PathBuf),
+    /// The thumbnail for this image was written.
+    Finished(PathBuf),
+    /// This image couldn't be thumbnailed, for the given reason.
+    Failed(PathBuf, String),
+}
+
+/// Like [`make_thumbnails`], but sends a [`ThumbnailEvent`] down `tx` as each
+/// job starts and then as it finishes or fails, so a UI can show progress.
+///
+/// Every job gets exactly one `Finished` or `Failed` event. Events arrive in
+/// whatever order the jobs run. If the receiver is dropped, the remaining
+/// jobs still run; their events are just discarded.
+pub fn make_thumbnails_with_progress(
+    jobs: &[(PathBuf, PathBuf)],
+    max_width: u32,
+    max_height: u32,
+    tx: Sender<ThumbnailEvent>,
+) {
+    jobs.par_iter()
+        .for_each_with(tx, |tx, (image_path, thumbnail_path)| {
+            let _ = tx.send(ThumbnailEvent::Started(image_path.clone()));
+            let event = match make_thumbnail(image_path, thumbnail_path, max_width, max_height, None) {
+                Ok(()) => ThumbnailEvent::Finished(image_path.clone()),
+                Err(err) => ThumbnailEvent::Failed(image_path.clone(), format!("{err:#}")),
+            };
+            let _ = tx.send(event);
+        });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    /// Writes a simple gradient image of the given size to `path`.
+    fn write_test_image(path: &Path, width: u32, height: u32) {
+        RgbImage::from_fn(width, height, |x, y| {
+            Rgb([(x % 256) as u8, (y % 256) as u8, 128])
+        })
+        .save(path)
+        .unwrap();
+    }
+
+    #[test]
+    fn test_make_thumbnail() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("source.png");
+        let thumbnail = dir.path().join("thumbnail.png");
+        write_test_image(&source, 400, 200);
+
+        make_thumbnail(&source, &thumbnail, DEFAULT_SIZE, DEFAULT_SIZE, None).unwrap();
+        let thumbnail = image::open(thumbnail).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (100, 50));
+    }
+
+    #[test]
+    fn test_thumbnail_sizes() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("source.png");
+        write_test_image(&source, 640, 480);
+
+        for (max_width, max_height) in [(100, 100), (320, 50), (64, 640), (1000, 1000)] {
+            let thumbnail = dir.path().join(format!("{max_width}x{max_height}.png"));
+            make_thumbnail(&source, &thumbnail, max_width, max_height, None).unwrap();
+            let thumbnail = image::open(thumbnail).unwrap();
+            let (width, height) = (thumbnail.width(), thumbnail.height());
+            assert!(
+                width <= max_width && height <= max_height,
+                "{width}x{height}"
+            );
+            // One side touches the box, and the 4:3 aspect ratio is kept
+            assert!(width == max_width || height == max_height);
+            assert!((width as f64 / height as f64 - 4.0 / 3.0).abs() < 0.05);
+        }
+    }
+
+    #[test]
+    fn test_explicit_formats() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("source.png");
+        write_test_image(&source, 200, 200);
+
+        for format in [ImageFormat::Png, ImageFormat::Jpeg] {
+            // No extension, so only the explicit format says what to write
+            let thumbnail = dir.path().join(format!("thumbnail-{format:?}"));
+            make_thumbnail(&source, &thumbnail, 50, 50, Some(format)).unwrap();
+            let bytes = std::fs::read(&thumbnail).unwrap();
+            assert_eq!(image::guess_format(&bytes).unwrap(), format);
+        }
+
+        // The explicit format wins over the extension
+        let thumbnail = dir.path().join("thumbnail.png");
+        make_thumbnail(&source, &thumbnail, 50, 50, Some(ImageFormat::Jpeg)).unwrap();
+        let bytes = std::fs::read(&thumbnail).unwrap();
+        assert_eq!(image::guess_format(&bytes).unwrap(), ImageFormat::Jpeg);
+    }
+
+    #[test]
+    fn test_unknown_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("source.png");
+        write_test_image(&source, 20, 20);
+
+        let err = make_thumbnail(&source, dir.path().join("thumbnail"), 10, 10, None).unwrap_err();
+        assert!(
+            err.to_string().contains("Can't tell which image format"),
+            "{err}"
+        );
+        let err =
+            make_thumbnail(&source, dir.path().join("thumbnail.xyz"), 10, 10, None).unwrap_err();
+        assert!(
+            err.to_string().contains("Can't tell which image format"),
+            "{err}"
+        );
+        let err =
+            make_thumbnail(&source, dir.path().join("thumbnail.hdr"), 10, 10, None).unwrap_err();
+        assert!(err.to_string().contains("can't be written as Hdr"), "{err}");
+    }
+
+    #[test]
+    fn test_thumbnail_bytes() {
+        let mut png = Vec::new();
+        RgbImage::from_fn(80, 40, |x, _| Rgb([x as u8, 0, 0]))
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+
+        let thumbnail = thumbnail_bytes(&png, 20, 20, ImageFormat::Png).unwrap();
+        assert_eq!(image::guess_format(&thumbnail).unwrap(), ImageFormat::Png);
+        let decoded = image::load_from_memory(&thumbnail).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (20, 10));
+
+        let jpeg = thumbnail_bytes(&png, 20, 20, ImageFormat::Jpeg).unwrap();
+        assert_eq!(image::guess_format(&jpeg).unwrap(), ImageFormat::Jpeg);
+
+        assert!(thumbnail_bytes(b"not an image", 20, 20, ImageFormat::Png).is_err());
+    }
+
+    #[test]
+    fn test_pixel_limit() {
+        let mut png = Vec::new();
+        RgbImage::new(80, 40)
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+
+        assert!(decode(&png).is_ok());
+        assert!(decode_with_limit(&png, 80 * 40).is_ok());
+        let err = decode_with_limit(&png, 80 * 40 - 1).unwrap_err();
+        assert!(err.to_string().contains("80x40"), "{err}");
+    }
+
+    #[test]
+    fn test_rejects_decompression_bomb() {
+        // A farbfeld header claiming 50000x50000 pixels, with no pixel data
+        let mut bomb = b"farbfeld".to_vec();
+        bomb.extend_from_slice(&50_000u32.to_be_bytes());
+        bomb.extend_from_slice(&50_000u32.to_be_bytes());
+
+        let err = thumbnail_bytes(&bomb, 10, 10, ImageFormat::Png).unwrap_err();
+        assert!(err.to_string().contains("50000x50000"), "{err}");
+    }
+
+    /// Inserts an EXIF segment holding just an orientation tag into a JPEG.
+    fn with_orientation(jpeg: &[u8], orientation: u16) -> Vec<u8> {
+        let mut tiff = b"MM\0\x2a\0\0\0\x08".to_vec(); // big-endian, first IFD at offset 8
+        tiff.extend_from_slice(&1u16.to_be_bytes()); // one entry
+        tiff.extend_from_slice(&0x0112u16.to_be_bytes()); // Orientation
+        tiff.extend_from_slice(&3u16.to_be_bytes()); // SHORT
+        tiff.extend_from_slice(&1u32.to_be_bytes()); // one value
+        tiff.extend_from_slice(&orientation.to_be_bytes());
+        tiff.extend_from_slice(&[0, 0]);
+        tiff.extend_from_slice(&0u32.to_be_bytes()); // no next IFD
+
+        let mut app1 = vec![0xFF, 0xE1];
+        app1.extend_from_slice(&(2 + 6 + tiff.len() as u16).to_be_bytes());
+        app1.extend_from_slice(b"Exif\0\0");
+        app1.extend_from_slice(&tiff);
+
+        // Straight after the start-of-image marker
+        [&jpeg[..2], &app1, &jpeg[2..]].concat()
+    }
+
+    #[test]
+    fn test_exif_orientation() {
+        let mut jpeg = Vec::new();
+        RgbImage::from_fn(200, 100, |x, _| Rgb([x as u8, 0, 0]))
+            .write_to(&mut Cursor::new(&mut jpeg), ImageFormat::Jpeg)
+            .unwrap();
+        assert_eq!(exif_orientation(&jpeg), None);
+        let upright =
+            image::load_from_memory(&thumbnail_bytes(&jpeg, 100, 100, ImageFormat::Png).unwrap())
+                .unwrap();
+        assert_eq!((upright.width(), upright.height()), (100, 50));
+
+        // Tag 6: the camera was turned 90 degrees, so the thumbnail turns portrait
+        let rotated = with_orientation(&jpeg, 6);
+        assert_eq!(exif_orientation(&rotated), Some(6));
+        let thumbnail = thumbnail_bytes(&rotated, 100, 100, ImageFormat::Png).unwrap();
+        let thumbnail = image::load_from_memory(&thumbnail).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (50, 100));
+    }
+
+    #[test]
+    fn test_apply_orientation() {
+        // A 2x1 image with a red pixel on the left
+        let image = DynamicImage::ImageRgb8(RgbImage::from_fn(2, 1, |x, _| {
+            if x == 0 {
+                Rgb([255, 0, 0])
+            } else {
+                Rgb([0, 0, 0])
+            }
+        }));
+        let red_at = |image: &DynamicImage| {
+            let image = image.to_rgb8();
+            let (x, y, _) = image
+                .enumerate_pixels()
+                .find(|(_, _, p)| p[0] == 255)
+                .unwrap();
+            (image.width(), image.height(), x, y)
+        };
+        assert_eq!(
+            red_at(&apply_orientation(image.clone(), None)),
+            (2, 1, 0, 0)
+        );
+        assert_eq!(
+            red_at(&apply_orientation(image.clone(), Some(1))),
+            (2, 1, 0, 0)
+        );
+        assert_eq!(
+            red_at(&apply_orientation(image.clone(), Some(2))),
+            (2, 1, 1, 0)
+        );
+        assert_eq!(
+            red_at(&apply_orientation(image.clone(), Some(6))),
+            (1, 2, 0, 0)
+        );
+        assert_eq!(
+            red_at(&apply_orientation(image.clone(), Some(8))),
+            (1, 2, 0, 1)
+        );
+    }
+
+    #[test]
+    fn test_make_thumbnails() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = |name: &str| dir.path().join(name);
+        write_test_image(&path("one.png"), 300, 300);
+        write_test_image(&path("two.jpg"), 120, 80);
+        std::fs::write(path("garbage.png"), b"definitely not an image").unwrap();
+
+        let inputs = vec![
+            (path("one.png"), path("one_thumb.png")),
+            (path("garbage.png"), path("garbage_thumb.png")),
+            (path("two.jpg"), path("two_thumb.jpg")),
+        ];
+        let results = make_thumbnails(&inputs, DEFAULT_SIZE, DEFAULT_SIZE);
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(
+            results.iter().filter(|(_, result)| result.is_ok()).count(),
+            2
+        );
+        let (failed, _) = results.iter().find(|(_, result)| result.is_err()).unwrap();
+        assert_eq!(failed, &path("garbage.png"));
+        assert!(path("one_thumb.png").exists());
+        assert!(path("two_thumb.jpg").exists());
+        assert!(!path("garbage_thumb.png").exists());
+    }
+
+    #[test]
+    fn test_make_thumbnails_with_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = |name: &str| dir.path().join(name);
+        let mut jobs = Vec::new();
+        for i in 0..5 {
+            write_test_image(&path(&format!("{i}.png")), 60, 40);
+            jobs.push((path(&format!("{i}.png")), path(&format!("{i}_thumb.png"))));
+        }
+        std::fs::write(path("garbage.png"), b"definitely not an image").unwrap();
+        jobs.push((path("garbage.png"), path("garbage_thumb.png")));
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        make_thumbnails_with_progress(&jobs, 20, 20, tx);
+        // The sender has been dropped, so this ends once every event is read
+        let events: Vec<ThumbnailEvent> = rx.iter().collect();
+
+        assert_eq!(events.len(), jobs.len() * 2);
+        for (image_path, _) in &jobs {
+            let started = events.iter().position(|e| e == &ThumbnailEvent::Started(image_path.clone()));
+            let finished: Vec<usize> = events
+                .iter()
+                .enumerate()
+                .filter(|(_, e)| match e {
+                    ThumbnailEvent::Finished(p) | ThumbnailEvent::Failed(p, _) => p == image_path,
+                    ThumbnailEvent::Started(_) => false,
+                })
+                .map(|(i, _)| i)
+                .collect();
+            assert_eq!(finished.len(), 1, "one terminal event for {}", image_path.display());
+            assert!(started.unwrap() < finished[0]);
+        }
+        let failed: Vec<_> = events
+            .iter()
+            .filter_map(|e| match e {
+                ThumbnailEvent::Failed(p, reason) => Some((p, reason)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].0, &path("garbage.png"));
+        assert!(!failed[0].1.is_empty());
+    }
+
+    /// Serves one HTTP response on a local port, returning its URL.
+    fn serve_once(content_type: &str, body: Vec<u8>) -> String {
+        use std::io::{BufRead, BufReader, Write};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/image", listener.local_addr().unwrap());
+        let head = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        );
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            // Read the request headers, up to the blank line
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            let _ = stream.write_all(head.as_bytes());
+            let _ = stream.write_all(&body);
+        });
+        url
+    }
+
+    fn png_bytes(width: u32, height: u32) -> Vec<u8> {
+        let mut png = Vec::new();
+        RgbImage::from_fn(width, height, |x, y| Rgb([x as u8, y as u8, 0]))
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+        png
+    }
+
+    #[tokio::test]
+    async fn test_thumbnail_from_url() {
+        let url = serve_once("image/png", png_bytes(120, 60));
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("thumb.jpg");
+        thumbnail_from_url(&url, &out, 40, 40).await.unwrap();
+
+        let bytes = std::fs::read(&out).unwrap();
+        assert_eq!(image::guess_format(&bytes).unwrap(), ImageFormat::Jpeg);
+        let thumbnail = image::load_from_memory(&bytes).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (40, 20));
+    }
+
+    #[tokio::test]
+    async fn test_thumbnail_from_url_not_an_image() {
+        let url = serve_once("text/html", b"<html></html>".to_vec());
+        let dir = tempfile::tempdir().unwrap();
+        let err = thumbnail_from_url(&url, &dir.path().join("thumb.png"), 40, 40)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("isn't an image"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_image_too_large() {
+        let png = png_bytes(50, 50);
+        let limit = png.len() - 1;
+        let url = serve_once("image/png", png);
+        let err = fetch_image(&url, limit).await.unwrap_err();
+        assert!(err.to_string().contains("larger than the limit"), "{err}");
+    }
+
+    #[cfg(feature = "webp")]
+    #[test]
+    fn test_webp() {
+        let png = png_bytes(120, 80);
+        let webp = thumbnail_bytes(&png, 60, 60, ThumbnailFormat::WebP.into()).unwrap();
+        assert_eq!(image::guess_format(&webp).unwrap(), ImageFormat::WebP);
+        let decoded = image::load_from_memory(&webp).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (60, 40));
+        assert!(ThumbnailFormat::WebP.is_available());
+
+        // By extension too
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("source.png");
+        write_test_image(&source, 120, 80);
+        make_thumbnail(&source, dir.path().join("thumb.webp"), 30, 30, None).unwrap();
+        let thumbnail = image::open(dir.path().join("thumb.webp")).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (30, 20));
+    }
+
+    #[cfg(feature = "avif")]
+    #[test]
+    fn test_avif() {
+        let avif = thumbnail_bytes(&png_bytes(64, 64), 32, 32, ThumbnailFormat::Avif.into()).unwrap();
+        assert_eq!(image::guess_format(&avif).unwrap(), ImageFormat::Avif);
+    }
+
+    #[cfg(not(feature = "avif"))]
+    #[test]
+    fn test_avif_needs_feature() {
+        assert!(!ThumbnailFormat::Avif.is_available());
+        let err = thumbnail_bytes(&png_bytes(8, 8), 4, 4, ThumbnailFormat::Avif.into()).unwrap_err();
+        assert!(err.to_string().contains("`avif` feature"), "{err}");
+    }
+
+    #[test]
+    fn test_web_thumbnail() {
+        let png = png_bytes(100, 50);
+        let (thumbnail, format) = web_thumbnail(&png, 20, 20, None).unwrap();
+        assert_eq!(format, ThumbnailFormat::Png);
+        assert_eq!(image::guess_format(&thumbnail).unwrap(), ImageFormat::Png);
+
+        let (thumbnail, format) = web_thumbnail(&png, 20, 20, Some(ThumbnailFormat::Jpeg)).unwrap();
+        assert_eq!(format, ThumbnailFormat::Jpeg);
+        assert_eq!(image::guess_format(&thumbnail).unwrap(), ImageFormat::Jpeg);
+
+        // GIF isn't a thumbnail format, so it becomes a PNG
+        let mut gif = Vec::new();
+        RgbImage::new(10, 10)
+            .write_to(&mut Cursor::new(&mut gif), ImageFormat::Gif)
+            .unwrap();
+        assert_eq!(web_thumbnail(&gif, 5, 5, None).unwrap().1, ThumbnailFormat::Png);
+    }
+
+    #[test]
+    fn test_thumbnail_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("photos");
+        let dst = dir.path().join("thumbs");
+        std::fs::create_dir_all(src.join("2023/summer")).unwrap();
+        write_test_image(&src.join("top.png"), 200, 100);
+        write_test_image(&src.join("2023/spring.jpg"), 100, 200);
+        write_test_image(&src.join("2023/summer/beach.png"), 300, 300);
+        std::fs::write(src.join("2023/notes.txt"), "not an image").unwrap();
+        std::fs::write(src.join("2023/summer/broken.png"), "not an image either").unwrap();
+
+        let results = thumbnail_directory(&src, &dst, 50, 50).unwrap();
+        let paths: Vec<&Path> = results.iter().map(|(path, _)| path.as_path()).collect();
+        assert_eq!(
+            paths,
+            [
+                src.join("2023/spring.jpg"),
+                src.join("2023/summer/beach.png"),
+                src.join("2023/summer/broken.png"),
+                src.join("top.png"),
+            ]
+        );
+        assert_eq!(results.iter().filter(|(_, result)| result.is_ok()).count(), 3);
+        assert!(results[2].1.is_err());
+
+        let thumbnail = image::open(dst.join("2023/spring.jpg")).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (25, 50));
+        assert!(dst.join("2023/summer/beach.png").exists());
+        assert!(dst.join("top.png").exists());
+        assert!(!dst.join("2023/notes.txt").exists());
+    }
+
+    #[test]
+    fn test_thumbnail_directory_inside_source() {
+        let dir = tempfile::tempdir().unwrap();
+        write_test_image(&dir.path().join("one.png"), 100, 100);
+        let dst = dir.path().join("thumbs");
+
+        assert_eq!(thumbnail_directory(dir.path(), &dst, 10, 10).unwrap().len(), 1);
+        // The second run doesn't pick up the first run's thumbnails
+        assert_eq!(thumbnail_directory(dir.path(), &dst, 10, 10).unwrap().len(), 1);
+        assert!(!dst.join("thumbs").exists());
+
+        assert!(thumbnail_directory(&dir.path().join("missing"), &dst, 10, 10).is_err());
+    }
+
+    #[test]
+    fn test_thumbnail_format() {
+        assert!(ThumbnailFormat::Png.is_available());
+        assert!(ThumbnailFormat::Jpeg.is_available());
+        assert_eq!(ImageFormat::from(ThumbnailFormat::WebP), ImageFormat::WebP);
+        assert_eq!(ThumbnailFormat::WebP.mime_type(), "image/webp");
+        assert_eq!(ThumbnailFormat::try_from(ImageFormat::Jpeg).unwrap(), ThumbnailFormat::Jpeg);
+        assert!(ThumbnailFormat::try_from(ImageFormat::Gif).is_err());
+    }
+
+    #[test]
+    #[tracing_test::traced_test]
+    fn test_records_span() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("source.png");
+        let thumbnail = dir.path().join("thumbnail.png");
+        write_test_image(&source, 400, 200);
+
+        make_thumbnail(&source, &thumbnail, DEFAULT_SIZE, DEFAULT_SIZE, None).unwrap();
+        assert!(logs_contain("make_thumbnail{"));
+        assert!(logs_contain(&format!("input={}", source.display())));
+        assert!(logs_contain(&format!("output={}", thumbnail.display())));
+        assert!(logs_contain("source_width=400 source_height=200"));
+        assert!(logs_contain("elapsed_ms="));
+        assert!(logs_contain("Thumbnail created"));
+        assert!(!logs_contain("ERROR"));
+
+        assert!(make_thumbnail(dir.path().join("missing.png"), &thumbnail, 10, 10, None).is_err());
+        assert!(logs_contain("ERROR"));
+    }
+}
@@ -1,23 +1,116 @@
-fn make_thumbnail(image_path: &str, thumbnail_path: &str) -> anyhow::Result<()> {
-    let image_bytes: Vec<u8> = std::fs::read(image_path)?;
-    let image = if let Ok(format) = image::guess_format(&image_bytes) {
-        image::load_from_memory_with_format(&image_bytes, format)?
-    } else {
-        image::load_from_memory(&image_bytes)?
-    };
-    let thumbnail = image.thumbnail(100, 100);
-    thumbnail.save(thumbnail_path)?;
-    Ok(())
+use clap::Parser;
+use std::path::PathBuf;
+use thumbnailer::{make_thumbnail, thumbnail_directory, ThumbnailFormat, DEFAULT_SIZE};
+use tracing_subscriber::fmt::format::FmtSpan;
+
+/// Makes thumbnails that keep the image's aspect ratio and fit within
+/// width x height.
+#[derive(Parser, Debug)]
+#[command(version)]
+struct Args {
+    /// The image to thumbnail, or with --recursive, a directory of them
+    input: PathBuf,
+
+    /// Where to write the thumbnail, or with --recursive, a directory for them
+    output: PathBuf,
+
+    /// The widest the thumbnail may be, in pixels
+    #[arg(long, default_value_t = DEFAULT_SIZE, value_parser = clap::value_parser!(u32).range(1..))]
+    width: u32,
+
+    /// The tallest the thumbnail may be, in pixels
+    #[arg(long, default_value_t = DEFAULT_SIZE, value_parser = clap::value_parser!(u32).range(1..))]
+    height: u32,
+
+    /// The format to write (png, jpeg, webp or avif). Defaults to the one
+    /// matching the output's extension.
+    #[arg(long, value_parser = parse_format, conflicts_with = "recursive")]
+    format: Option<ThumbnailFormat>,
+
+    /// Thumbnail every image under the input directory, mirroring its layout
+    #[arg(long)]
+    recursive: bool,
+}
+
+/// Accepts a format by name or by its usual file extension.
+fn parse_format(name: &str) -> Result<ThumbnailFormat, String> {
+    image::ImageFormat::from_extension(name.to_lowercase())
+        .ok_or_else(|| format!("unknown format {name}"))
+        .and_then(|format| ThumbnailFormat::try_from(format).map_err(|err| err.to_string()))
+}
+
+/// Logs to stderr, so callers reading our stdout only see the results. Each
+/// thumbnail job is logged as its span closes, with everything it recorded.
+fn init_tracing() {
+    tracing_subscriber::fmt()
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(std::io::stderr)
+        .init();
 }
 
 fn main() {
-    let args = std::env::args().collect::<Vec<_>>();
-    if args.len() != 3 {
-        println!("Usage: thumbnailer <image> <thumbnail>");
-    } else {
-        match make_thumbnail(&args[1], &args[2]) {
-            Ok(_) => println!("Thumbnail created"),
-            Err(err) => println!("Error: {}", err),
+    init_tracing();
+    let args = Args::parse();
+
+    if args.recursive {
+        match thumbnail_directory(&args.input, &args.output, args.width, args.height) {
+            Ok(results) => {
+                for (path, result) in &results {
+                    if let Err(err) = result {
+                        println!("{}: {err}", path.display());
+                    }
+                }
+                let made = results.iter().filter(|(_, result)| result.is_ok()).count();
+                println!("Created {made} of {} thumbnails", results.len());
+            }
+            Err(err) => println!("Error: {err:#}"),
+        }
+        return;
+    }
+
+    let format = args.format.map(Into::into);
+    match make_thumbnail(&args.input, &args.output, args.width, args.height, format) {
+        Ok(_) => println!("Thumbnail created"),
+        Err(err) => println!("Error: {}", err),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_args() {
+        let args = Args::parse_from(vec![
+            "thumbnailer", "photo.jpg", "thumb.webp", "--width", "320", "--height", "240",
+            "--format", "webp",
+        ]);
+        assert_eq!(args.input, PathBuf::from("photo.jpg"));
+        assert_eq!(args.output, PathBuf::from("thumb.webp"));
+        assert_eq!((args.width, args.height), (320, 240));
+        assert_eq!(args.format, Some(ThumbnailFormat::WebP));
+        assert!(!args.recursive);
+    }
+
+    #[test]
+    fn test_defaults() {
+        let args = Args::parse_from(vec!["thumbnailer", "--recursive", "photos", "thumbs"]);
+        assert_eq!(args.input, PathBuf::from("photos"));
+        assert_eq!((args.width, args.height), (DEFAULT_SIZE, DEFAULT_SIZE));
+        assert_eq!(args.format, None);
+        assert!(args.recursive);
+    }
+
+    #[test]
+    fn test_rejects_bad_args() {
+        for bad in [
+            vec!["thumbnailer", "photo.jpg"],
+            vec!["thumbnailer", "photo.jpg", "thumb.jpg", "--width", "0"],
+            vec!["thumbnailer", "photo.jpg", "thumb.jpg", "--height", "tall"],
+            vec!["thumbnailer", "photo.jpg", "thumb.jpg", "--format", "gif"],
+            vec!["thumbnailer", "--recursive", "a", "b", "--format", "png"],
+        ] {
+            assert!(Args::try_parse_from(&bad).is_err(), "{bad:?}");
         }
     }
 }
@@ -0,0 +1,225 @@
+//! Subscriber setup shared by the tracing examples.
+
+use std::path::PathBuf;
+use tracing::subscriber::SetGlobalDefaultError;
+use tracing::Subscriber;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::filter::{self, EnvFilter, LevelFilter};
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::{Layered, SubscriberExt};
+use tracing_subscriber::{Layer, Registry};
+
+/// How log lines are written, chosen with the `LOG_FORMAT` environment
+/// variable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    /// One JSON object per line, for log collectors.
+    Json,
+    /// Abbreviated single-line output.
+    #[default]
+    Compact,
+    /// Multi-line, human-friendly output for development.
+    Pretty,
+}
+
+impl LogFormat {
+    /// Reads `LOG_FORMAT` (`json`, `compact` or `pretty`, in any case).
+    /// Unset or unrecognised values give the default, `Compact`.
+    pub fn from_env() -> Self {
+        Self::parse(std::env::var("LOG_FORMAT").ok().as_deref())
+    }
+
+    fn parse(value: Option<&str>) -> Self {
+        match value.map(|v| v.trim().to_ascii_lowercase()).as_deref() {
+            Some("json") => LogFormat::Json,
+            Some("pretty") => LogFormat::Pretty,
+            _ => LogFormat::Compact,
+        }
+    }
+}
+
+fn filter_builder() -> filter::Builder {
+    EnvFilter::builder().with_default_directive(LevelFilter::INFO.into())
+}
+
+/// A filter from the `RUST_LOG` environment variable, e.g.
+/// `RUST_LOG=axum_tracing=debug,info`. Anything not matched by a directive,
+/// or everything if `RUST_LOG` is unset, is logged at `INFO`.
+pub fn env_filter() -> EnvFilter {
+    filter_builder().from_env_lossy()
+}
+
+/// Parses `RUST_LOG`-style directives, with the same `INFO` fallback as
+/// [`env_filter`].
+pub fn parse_filter(directives: &str) -> Result<EnvFilter, filter::ParseError> {
+    filter_builder().parse(directives)
+}
+
+/// Where logs go, in addition to stdout.
+#[derive(Debug, Clone)]
+pub struct TracingOptions {
+    /// If set, logs are also written to a file in this directory, starting
+    /// a new file each day.
+    pub file_dir: Option<PathBuf>,
+    /// Log file names start with this, followed by the date.
+    pub file_prefix: String,
+}
+
+impl Default for TracingOptions {
+    fn default() -> Self {
+        TracingOptions {
+            file_dir: None,
+            file_prefix: "app.log".to_string(),
+        }
+    }
+}
+
+type Base = Layered<EnvFilter, Registry>;
+
+/// A formatting layer writing in `format` with our usual settings.
+fn fmt_layer<W>(format: LogFormat, writer: W, ansi: bool) -> Box<dyn Layer<Base> + Send + Sync>
+where
+    W: for<'writer> MakeWriter<'writer> + Send + Sync + 'static,
+{
+    let layer = tracing_subscriber::fmt::layer()
+        .with_writer(writer)
+        .with_ansi(ansi)
+        // Display source code file paths
+        .with_file(true)
+        // Display source code line numbers
+        .with_line_number(true)
+        // Display the thread ID an event was recorded on
+        .with_thread_ids(true)
+        // Don't display the event's target (module path)
+        .with_target(false)
+        // Add span events
+        .with_span_events(FmtSpan::ENTER | FmtSpan::CLOSE);
+
+    match format {
+        LogFormat::Json => layer.json().boxed(),
+        // Use a more compact, abbreviated log format
+        LogFormat::Compact => layer.compact().boxed(),
+        LogFormat::Pretty => layer.pretty().boxed(),
+    }
+}
+
+/// Builds a subscriber writing to stdout in `format`, filtered by
+/// `RUST_LOG`.
+pub fn subscriber(format: LogFormat) -> Box<dyn Subscriber + Send + Sync> {
+    subscriber_with(format, &TracingOptions::default()).0
+}
+
+/// Like [`subscriber`], but also writing to a log file if `opts` asks for
+/// one. File writes happen on a background thread, which stops when the
+/// returned guard is dropped.
+pub fn subscriber_with(
+    format: LogFormat,
+    opts: &TracingOptions,
+) -> (Box<dyn Subscriber + Send + Sync>, Option<WorkerGuard>) {
+    let mut layers = vec![fmt_layer(format, std::io::stdout, true)];
+    let mut guard = None;
+    if let Some(dir) = &opts.file_dir {
+        let appender = tracing_appender::rolling::daily(dir, &opts.file_prefix);
+        let (writer, worker_guard) = tracing_appender::non_blocking(appender);
+        layers.push(fmt_layer(format, writer, false));
+        guard = Some(worker_guard);
+    }
+
+    let subscriber = tracing_subscriber::registry()
+        .with(env_filter())
+        .with(layers);
+    (Box::new(subscriber), guard)
+}
+
+/// Installs a global subscriber in the format given by `LOG_FORMAT`, with
+/// verbosity controlled by `RUST_LOG`, writing to stdout and optionally a
+/// rolling log file.
+///
+/// Keep the returned guard alive for as long as you're logging: dropping it
+/// flushes and closes the log file.
+///
+/// There can only be one global subscriber, so calling this again returns
+/// an error rather than replacing it.
+pub fn init_tracing(opts: TracingOptions) -> Result<Option<WorkerGuard>, SetGlobalDefaultError> {
+    let (subscriber, guard) = subscriber_with(LogFormat::from_env(), &opts);
+    tracing::subscriber::set_global_default(subscriber)?;
+    Ok(guard)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(LogFormat::parse(Some("json")), LogFormat::Json);
+        assert_eq!(LogFormat::parse(Some("PRETTY")), LogFormat::Pretty);
+        assert_eq!(LogFormat::parse(Some("compact")), LogFormat::Compact);
+        assert_eq!(LogFormat::parse(Some("fancy")), LogFormat::Compact);
+        assert_eq!(LogFormat::parse(None), LogFormat::Compact);
+    }
+
+    #[test]
+    fn test_every_format_builds() {
+        for format in [LogFormat::Json, LogFormat::Compact, LogFormat::Pretty] {
+            let subscriber = subscriber(format);
+            tracing::subscriber::with_default(subscriber, || tracing::info!("Hello, {format:?}"));
+        }
+    }
+
+    #[test]
+    fn test_parse_filter() {
+        use tracing::Level;
+
+        let filter = parse_filter("axum_tracing=debug,info").unwrap();
+        assert_eq!(filter.max_level_hint(), Some(LevelFilter::DEBUG));
+
+        let subscriber = tracing_subscriber::registry().with(filter);
+        tracing::subscriber::with_default(subscriber, || {
+            assert!(tracing::enabled!(target: "axum_tracing", Level::DEBUG));
+            assert!(!tracing::enabled!(target: "axum_tracing", Level::TRACE));
+            assert!(tracing::enabled!(target: "tower_http", Level::INFO));
+            assert!(!tracing::enabled!(target: "tower_http", Level::DEBUG));
+        });
+
+        assert!(parse_filter("axum_tracing=loud").is_err());
+    }
+
+    #[test]
+    fn test_default_filter_is_info() {
+        let filter = parse_filter("").unwrap();
+        assert_eq!(filter.max_level_hint(), Some(LevelFilter::INFO));
+    }
+
+    #[test]
+    fn test_log_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let opts = TracingOptions {
+            file_dir: Some(dir.path().to_path_buf()),
+            file_prefix: "test.log".to_string(),
+        };
+        let (subscriber, guard) = subscriber_with(LogFormat::Json, &opts);
+        tracing::subscriber::with_default(subscriber, || {
+            for n in 0..3 {
+                tracing::info!("Writing event {n}");
+            }
+        });
+        // Flushes the background writer
+        drop(guard);
+
+        let files: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(files.len(), 1);
+        let name = files[0].file_name().unwrap().to_str().unwrap();
+        assert!(name.starts_with("test.log."), "{name}");
+
+        let contents = std::fs::read_to_string(&files[0]).unwrap();
+        assert_eq!(contents.lines().count(), 3);
+        assert!(contents.contains("Writing event 2"));
+        // No colour codes in files
+        assert!(!contents.contains('\x1b'));
+    }
+}
@@ -1,29 +1,12 @@
-use tracing_subscriber::fmt::format::FmtSpan;
-
 #[tokio::main]
 async fn main() {
-    // Simple output
-    //let subscriber = tracing_subscriber::FmtSubscriber::new();
-
-    // Detailed Output
-    let subscriber = tracing_subscriber::fmt()
-        .json()
-        // Use a more compact, abbreviated log format
-        .compact()
-        // Display source code file paths
-        .with_file(true)
-        // Display source code line numbers
-        .with_line_number(true)
-        // Display the thread ID an event was recorded on
-        .with_thread_ids(true)
-        // Don't display the event's target (module path)
-        .with_target(false)
-        // Add span events
-        .with_span_events(FmtSpan::ENTER | FmtSpan::CLOSE)
-        // Build the subscriber
-        .finish();
-
-    tracing::subscriber::set_global_default(subscriber).unwrap();
+    // Set LOG_FORMAT to json, compact or pretty
+    // Set LOG_DIR to also write logs to a daily file there
+    let opts = tokio_tracing::TracingOptions {
+        file_dir: std::env::var_os("LOG_DIR").map(Into::into),
+        file_prefix: "tokio_tracing.log".to_string(),
+    };
+    let _guard = tokio_tracing::init_tracing(opts).unwrap();
 
     tracing::info!("Hello World!");
     tracing::warn!("Hello World!");
@@ -0,0 +1,11 @@
+//! `init_tracing` installs a process-wide subscriber, so it's tested in its
+//! own binary where that can't leak into other tests.
+
+use tokio_tracing::{init_tracing, TracingOptions};
+
+#[test]
+fn test_init_tracing_only_once() {
+    let guard = init_tracing(TracingOptions::default()).unwrap();
+    assert!(guard.is_none());
+    assert!(init_tracing(TracingOptions::default()).is_err());
+}